@@ -1,25 +1,144 @@
+use crate::recent_projects::{PathMapping, ProjectFilter, SortOrder};
 use std::path::PathBuf;
 use structopt_derive::StructOpt;
 
 #[derive(StructOpt)]
 pub enum Command {
     /// Specify a project path to open
-    Path { path: PathBuf },
+    Path {
+        path: PathBuf,
+        /// Tags to give the project in the recent projects list
+        #[structopt(long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+    },
 
-    /// Specify a recent project index to open
-    Open { index: usize },
+    /// Specify a recent project ID, index or name to open
+    Open { project: String },
 
-    /// Specify a recent project index to remove
-    Remove { index: usize },
+    /// Specify a recent project ID, index or name to remove
+    Remove { project: String },
 
-    /// Clears all recent projects
-    Clear,
+    /// Clears all recent projects except the pinned ones
+    Clear {
+        /// Also clear the pinned projects
+        #[structopt(long)]
+        all: bool,
+        /// Don't ask for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+
+    /// Undo the last remove, clear, prune or import of recent projects
+    Undo,
+
+    /// Pin a recent project, given by ID, index or name, to the top of the list
+    Pin { project: String },
+
+    /// Unpin a recent project given by ID, index or name
+    Unpin { project: String },
+
+    /// Give a recent project, given by ID, index or name, a name to open it by
+    Alias {
+        project: String,
+        /// The new name, the alias is removed if it is left out
+        name: Option<String>,
+    },
+
+    /// Remove recent projects whose directory no longer exists
+    Prune {
+        /// Only list the projects that would be removed
+        #[structopt(long)]
+        dry_run: bool,
+    },
+
+    /// Tag a recent project, given by ID, index or name
+    Tag {
+        project: String,
+        #[structopt(required = true)]
+        tags: Vec<String>,
+        /// Remove the tags instead of adding them
+        #[structopt(long)]
+        remove: bool,
+    },
 
     /// List recent projects
-    Options,
+    #[structopt(alias = "list")]
+    Options {
+        /// Order of the list: frecency, recent, frequent or name
+        #[structopt(long, default_value = "frecency")]
+        sort: SortOrder,
+        /// Show everything recorded about each project
+        #[structopt(short, long)]
+        long: bool,
+        #[structopt(flatten)]
+        filter: FilterOptions,
+    },
 
     /// Interactive menu to select recent project to open
-    Recent,
+    Recent {
+        /// Order of the menu: frecency, recent, frequent or name
+        #[structopt(long, default_value = "frecency")]
+        sort: SortOrder,
+        #[structopt(flatten)]
+        filter: FilterOptions,
+    },
+
+    /// Show how often projects were opened, per project, type and week
+    Stats {
+        /// Only count the last few weeks
+        #[structopt(long)]
+        weeks: Option<u64>,
+    },
+
+    /// Move the recent projects list between machines
+    History(HistoryCommand),
+
+    /// Run the setup wizard to configure IDE and editor paths
+    Setup,
+
+    /// Work with the configuration file
+    Config(ConfigCommand),
+}
+
+#[derive(StructOpt)]
+pub struct FilterOptions {
+    /// Only show projects with this tag, can be given several times
+    #[structopt(long = "tag", number_of_values = 1)]
+    pub tags: Vec<String>,
+
+    /// Only show projects of this type
+    #[structopt(long = "type")]
+    pub project_type: Option<String>,
+}
+
+impl From<FilterOptions> for ProjectFilter {
+    fn from(options: FilterOptions) -> Self {
+        ProjectFilter {
+            tags: options.tags,
+            project_type: options.project_type,
+        }
+    }
+}
+
+#[derive(StructOpt)]
+pub enum HistoryCommand {
+    /// Print the recent projects, with their aliases, tags and pins, as JSON
+    Export,
+
+    /// Merge the recent projects from a file written by `history export`
+    Import {
+        file: PathBuf,
+        /// Move the projects under one directory to another, as from=to, can be given several
+        /// times
+        #[structopt(long = "map", number_of_values = 1)]
+        mappings: Vec<PathMapping>,
+    },
+}
+
+#[derive(StructOpt)]
+pub enum ConfigCommand {
+    /// Print the JSON Schema of the configuration file
+    Schema,
 }
 
 #[derive(StructOpt)]
@@ -28,6 +147,14 @@ pub enum Command {
     about = "A tool to open development project workspaces"
 )]
 pub struct Cli {
+    /// Configuration profile to use instead of the default one
+    #[structopt(long, env = "DEVENV_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Wait for the IDE to close and record how long the project was open
+    #[structopt(long)]
+    pub wait: bool,
+
     #[structopt(subcommand)]
     pub command: Option<Command>,
 }
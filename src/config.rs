@@ -1,49 +1,244 @@
+use crate::launch::{self, LaunchStep};
+use crate::utils;
+use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
 use std::path::{Path, PathBuf};
 use std::{env, fs};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Names of the per-platform sections, matching `std::env::consts::OS`
+const OS_SECTIONS: &[&str] = &["windows", "linux", "macos"];
+/// Keys holding a single path, which are left unset rather than overridden when empty
+const PATH_KEYS: &[&str] = &["ide_path", "editor_base_path", "json_editor_path"];
+/// Written on first run. Only the paths to fill in are listed, so that the launch steps,
+/// history limits and anything else still come from the defaults or the included files.
+const DEFAULT_CONFIG: &str = r#"[rust]
+ide_path = ""
+
+[unity]
+editor_base_path = ""
+json_editor_path = ""
+"#;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RustConfig {
     pub ide_path: PathBuf,
+    /// Steps run, in order, when a Rust project is opened
+    #[serde(default = "launch::default_rust_steps")]
+    pub launch: Vec<LaunchStep>,
+    /// Environment variables for the processes launched for Rust projects
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UnityConfig {
     pub editor_base_path: PathBuf,
+    /// Further folders holding one Unity editor per version folder
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub install_roots: Vec<PathBuf>,
     pub json_editor_path: PathBuf,
+    /// Steps run, in order, when a Unity project is opened
+    #[serde(default = "launch::default_unity_steps")]
+    pub launch: Vec<LaunchStep>,
+    /// Environment variables for the processes launched for Unity projects
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// A project type defined in the configuration rather than built into devenv.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CustomProjectConfig {
+    /// Files or folders that must all exist in a directory for it to be of this type
+    pub markers: Vec<PathBuf>,
+    #[serde(default)]
+    pub ide_path: PathBuf,
+    /// Steps run, in order, when a project of this type is opened
+    #[serde(default = "launch::default_custom_steps")]
+    pub launch: Vec<LaunchStep>,
+    /// Environment variables for the processes launched for projects of this type
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Limits of the recent projects list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryConfig {
+    /// Number of recent projects to keep, 0 keeps every project
+    #[serde(default = "default_history_limit")]
+    pub limit: usize,
+    /// Number of recent projects to keep per project type, as caps within `limit`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub type_limits: BTreeMap<String, usize>,
+    /// Remove projects whose directory no longer exists whenever the list is loaded
+    #[serde(default)]
+    pub auto_prune: bool,
+}
+
+fn default_history_limit() -> usize {
+    10
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            limit: default_history_limit(),
+            type_limits: BTreeMap::new(),
+            auto_prune: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    /// Files whose settings are loaded first, so this file overrides them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
     pub rust: RustConfig,
     pub unity: UnityConfig,
+    /// Environment variables for every launched process
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Limits of the recent projects list
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// Additional project types, keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub project_types: BTreeMap<String, CustomProjectConfig>,
+    /// Profile used when none is given on the command line or in `DEVENV_PROFILE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Named sets of overrides layered on top of the `rust` and `unity` sections
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(with = "BTreeMap<String, serde_json::Map<String, serde_json::Value>>")]
+    pub profiles: BTreeMap<String, toml::Table>,
+    /// Name of the profile the configuration was resolved with
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+impl UnityConfig {
+    /// Install roots to scan for editors, starting with `editor_base_path`.
+    pub fn editor_roots(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        if !self.editor_base_path.as_os_str().is_empty() {
+            roots.push(self.editor_base_path.clone());
+        }
+        roots.extend(self.install_roots.iter().cloned());
+        roots
+    }
 }
 
 impl Config {
-    pub fn from_file(file: &Path) -> Result<Self, config::ConfigError> {
-        let settings = config::Config::builder()
-            .add_source(config::File::with_name(file.to_str().unwrap()))
-            .build()?;
-        settings.try_deserialize()
+    pub fn from_file(file: &Path, profile: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let mut table = load_with_includes(file, &mut Vec::new())?;
+
+        let profile = match profile {
+            Some(name) => Some(name.to_string()),
+            None => table
+                .get("default_profile")
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+        };
+
+        if let Some(name) = &profile {
+            let overrides = table
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name))
+                .and_then(|profile| profile.as_table())
+                .cloned()
+                .ok_or_else(|| format!("Profile '{}' is not defined", name))?;
+            merge_tables(&mut table, overrides);
+        }
+
+        let mut config: Config = toml::Value::Table(table).try_into()?;
+        config.profile = profile;
+        config.expand_paths();
+        Ok(config)
+    }
+
+    /// Expands `~` and environment variables in every path of the configuration.
+    fn expand_paths(&mut self) {
+        let expand = |path: &mut PathBuf| *path = utils::expand_path(path);
+
+        expand(&mut self.rust.ide_path);
+        expand(&mut self.unity.editor_base_path);
+        expand(&mut self.unity.json_editor_path);
+        self.unity.install_roots.iter_mut().for_each(expand);
+        for custom in self.project_types.values_mut() {
+            expand(&mut custom.ide_path);
+        }
+
+        let steps = self
+            .rust
+            .launch
+            .iter_mut()
+            .chain(self.unity.launch.iter_mut())
+            .chain(
+                self.project_types
+                    .values_mut()
+                    .flat_map(|c| c.launch.iter_mut()),
+            );
+        for step in steps {
+            step.kind.paths_mut().into_iter().for_each(expand);
+        }
     }
 
     pub fn create_default(file: &Path) -> Result<(), std::io::Error> {
-        let default_config = Config {
-            rust: RustConfig {
-                ide_path: PathBuf::new(),
-            },
-            unity: UnityConfig {
-                editor_base_path: PathBuf::new(),
-                json_editor_path: PathBuf::new(),
-            },
+        utils::write_atomic(file, DEFAULT_CONFIG.as_bytes())
+    }
+
+    /// Writes the keys of the `rust` and `unity` sections that differ from the file. When a
+    /// profile is active they are stored in that profile so the shared sections stay untouched.
+    /// Everything else in the document, including comments and formatting, is kept as it is.
+    pub fn save_to_file(&self, file: &Path) -> Result<(), Box<dyn Error>> {
+        // Held until the file is written so that concurrent saves don't lose each other's keys
+        let _lock = utils::lock_file(file)?;
+        let saved = Config::from_file(file, self.profile.as_deref())?;
+        let changes: Vec<(&str, toml::Value, toml::Value)> = [
+            (
+                "rust",
+                toml::Value::try_from(&saved.rust)?,
+                toml::Value::try_from(&self.rust)?,
+            ),
+            (
+                "unity",
+                toml::Value::try_from(&saved.unity)?,
+                toml::Value::try_from(&self.unity)?,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .collect();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut document: toml_edit::DocumentMut = fs::read_to_string(file)?.parse()?;
+        let target = match &self.profile {
+            Some(name) => {
+                let profiles = child_table(document.as_table_mut(), "profiles")?;
+                profiles.set_implicit(true);
+                let profile = child_table(profiles, name)?;
+                profile.set_implicit(true);
+                profile
+            }
+            None => document.as_table_mut(),
         };
-        let toml = toml::to_string(&default_config).unwrap();
-        fs::write(file, toml)
+        for (section, old, new) in &changes {
+            write_section_changes(child_table(target, section)?, old, new)?;
+        }
+
+        utils::write_atomic(file, document.to_string().as_bytes())?;
+        Ok(())
     }
 
-    pub fn save_to_file(&self, file: &Path) -> Result<(), std::io::Error> {
-        let toml = toml::to_string(self).unwrap();
-        fs::write(file, toml)
+    /// JSON Schema of the configuration file, generated from the configuration types so
+    /// editors with TOML schema support can complete and validate it.
+    pub fn json_schema() -> String {
+        let schema = schemars::schema_for!(Config);
+        serde_json::to_string_pretty(&schema).expect("Failed to serialize the JSON schema")
     }
 
     pub fn get_config_dir(app_name: &str) -> PathBuf {
@@ -59,7 +254,11 @@ impl Config {
         }
     }
 
-    pub fn get_config(config_dir: &PathBuf, config_path: &PathBuf) -> Result<Self, String> {
+    pub fn get_config(
+        config_dir: &PathBuf,
+        config_path: &PathBuf,
+        profile: Option<&str>,
+    ) -> Result<Self, String> {
         // Ensure the configuration directory exists
         if let Err(e) = fs::create_dir_all(config_dir) {
             return Err(format!("Failed to create config directory: {}", e));
@@ -72,15 +271,587 @@ impl Config {
                     e
                 ));
             }
-            println!(
+            eprintln!(
                 "Created default configuration file at {}",
                 config_path.display()
             );
         }
 
-        match Config::from_file(config_path) {
+        match Config::from_file(config_path, profile) {
             Ok(config) => Ok(config),
             Err(e) => Err(format!("Failed to load configuration: {}", e)),
         }
     }
 }
+
+/// Loads `file` on top of the files it includes. Include paths are relative to the including
+/// file, later includes override earlier ones, and missing includes are reported and skipped.
+/// `chain` holds the canonical paths of the files currently being included, so a file that
+/// includes itself is skipped while one included along several paths is loaded each time.
+fn load_with_includes(
+    file: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<toml::Table, Box<dyn Error>> {
+    let mut table: toml::Table = fs::read_to_string(file)?
+        .parse()
+        .map_err(|e| format!("{}: {}", file.display(), e))?;
+    chain.push(fs::canonicalize(file)?);
+    resolve_os_sections(&mut table);
+
+    let includes: Vec<PathBuf> = match table.remove("include") {
+        Some(toml::Value::Array(paths)) => paths
+            .iter()
+            .filter_map(|path| path.as_str())
+            .map(|path| utils::expand_path(Path::new(path)))
+            .collect(),
+        Some(_) => {
+            return Err(format!("'include' in {} must be a list of paths", file.display()).into())
+        }
+        None => Vec::new(),
+    };
+
+    let base_dir = file.parent().unwrap_or(Path::new(""));
+    let mut merged = toml::Table::new();
+    for include in includes {
+        let path = base_dir.join(&include);
+        match fs::canonicalize(&path) {
+            Err(_) => eprintln!("Included configuration file not found: {}", path.display()),
+            Ok(canonical) if chain.contains(&canonical) => {
+                eprintln!("Skipping recursive include: {}", path.display())
+            }
+            Ok(_) => merge_tables(&mut merged, load_with_includes(&path, chain)?),
+        }
+    }
+    chain.pop();
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Resolves the `windows`, `linux` and `macos` subtables of the `rust`, `unity` and custom
+/// project type sections, in the file itself and in every profile. The maps keyed by name
+/// are left alone, so a profile or project type may itself be named after a platform.
+fn resolve_os_sections(table: &mut toml::Table) {
+    for section in ["rust", "unity"] {
+        if let Some(toml::Value::Table(section)) = table.get_mut(section) {
+            resolve_os_section(section);
+        }
+    }
+    if let Some(toml::Value::Table(project_types)) = table.get_mut("project_types") {
+        for (_, project_type) in project_types.iter_mut() {
+            if let toml::Value::Table(project_type) = project_type {
+                resolve_os_section(project_type);
+            }
+        }
+    }
+    if let Some(toml::Value::Table(profiles)) = table.get_mut("profiles") {
+        for (_, profile) in profiles.iter_mut() {
+            if let toml::Value::Table(profile) = profile {
+                resolve_os_sections(profile);
+            }
+        }
+    }
+}
+
+/// Removes the per-platform subtables of `section`, merging the one for the current platform
+/// into it, so `[rust.linux]` overrides `[rust]` on Linux.
+fn resolve_os_section(section: &mut toml::Table) {
+    let mut current = None;
+    for os in OS_SECTIONS {
+        if let Some(toml::Value::Table(os_section)) = section.get(*os) {
+            if *os == env::consts::OS {
+                current = Some(os_section.clone());
+            }
+            section.remove(*os);
+        }
+    }
+    if let Some(os_section) = current {
+        merge_tables(section, os_section);
+    }
+}
+
+/// Returns the table stored under `key`, creating it if it's missing.
+fn child_table<'a>(
+    parent: &'a mut toml_edit::Table,
+    key: &str,
+) -> Result<&'a mut toml_edit::Table, Box<dyn Error>> {
+    parent
+        .entry(key)
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| format!("'{}' is not a table", key).into())
+}
+
+/// Writes the changes to a section like `write_changes`, except that keys set in the section
+/// for the current platform are changed there, since that value is the one in effect.
+fn write_section_changes(
+    section: &mut toml_edit::Table,
+    old: &toml::Value,
+    new: &toml::Value,
+) -> Result<(), Box<dyn Error>> {
+    let os_keys: Vec<String> = match section.get(env::consts::OS) {
+        Some(toml_edit::Item::Table(os_section)) => {
+            os_section.iter().map(|(key, _)| key.to_string()).collect()
+        }
+        _ => Vec::new(),
+    };
+    let (os_changes, changes) = split_keys(new, &os_keys);
+    if !os_keys.is_empty() {
+        write_changes(child_table(section, env::consts::OS)?, old, &os_changes)?;
+    }
+    write_changes(section, old, &changes)
+}
+
+/// Splits the keys of a table into those in `keys` and the rest.
+fn split_keys(value: &toml::Value, keys: &[String]) -> (toml::Value, toml::Value) {
+    let (mut matching, mut rest) = (toml::Table::new(), toml::Table::new());
+    if let Some(table) = value.as_table() {
+        for (key, value) in table {
+            if keys.contains(key) {
+                matching.insert(key.clone(), value.clone());
+            } else {
+                rest.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    (toml::Value::Table(matching), toml::Value::Table(rest))
+}
+
+/// Writes the keys of `new` whose value differs from `old` into `target`, descending into
+/// nested tables so only the changed leaves are touched.
+fn write_changes(
+    target: &mut toml_edit::Table,
+    old: &toml::Value,
+    new: &toml::Value,
+) -> Result<(), Box<dyn Error>> {
+    let Some(new) = new.as_table() else {
+        return Ok(());
+    };
+    for (key, new_value) in new {
+        let old_value = old.get(key);
+        if old_value == Some(new_value) {
+            continue;
+        }
+        match (new_value, target.get(key)) {
+            (toml::Value::Table(_), None | Some(toml_edit::Item::Table(_))) => write_changes(
+                child_table(target, key)?,
+                old_value.unwrap_or(&toml::Value::Table(toml::Table::new())),
+                new_value,
+            )?,
+            (_, Some(toml_edit::Item::Value(existing))) if !is_table_array(new_value) => {
+                let decor = existing.decor().clone();
+                let mut value: toml_edit::Value = new_value.to_string().parse()?;
+                *value.decor_mut() = decor;
+                target[key.as_str()] = toml_edit::Item::Value(value);
+            }
+            _ => {
+                target.insert(key, to_item(new_value)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_table_array(value: &toml::Value) -> bool {
+    matches!(value, toml::Value::Array(items) if !items.is_empty() && items.iter().all(toml::Value::is_table))
+}
+
+fn to_item(value: &toml::Value) -> Result<toml_edit::Item, Box<dyn Error>> {
+    match value {
+        toml::Value::Table(table) => {
+            let mut result = toml_edit::Table::new();
+            for (key, value) in table {
+                result.insert(key, to_item(value)?);
+            }
+            Ok(toml_edit::Item::Table(result))
+        }
+        toml::Value::Array(items) if is_table_array(value) => {
+            let mut result = toml_edit::ArrayOfTables::new();
+            for item in items {
+                if let toml_edit::Item::Table(table) = to_item(item)? {
+                    result.push(table);
+                }
+            }
+            Ok(toml_edit::Item::ArrayOfTables(result))
+        }
+        value => Ok(toml_edit::value(
+            value.to_string().parse::<toml_edit::Value>()?,
+        )),
+    }
+}
+
+/// Recursively merges `overlay` into `base`. Nested tables are merged key by key while any
+/// other value, including arrays, replaces the one in `base`. Empty paths count as unset.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table)
+            }
+            (Some(_), toml::Value::String(path))
+                if path.is_empty() && PATH_KEYS.contains(&key.as_str()) => {}
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const CONFIG: &str = r#"
+default_profile = "home"
+
+[rust]
+ide_path = "/usr/bin/rustrover"
+
+[unity]
+editor_base_path = "/opt/unity"
+json_editor_path = "/usr/bin/code"
+
+[profiles.home.rust]
+ide_path = "/usr/bin/zed"
+
+[profiles.work.unity]
+editor_base_path = "/mnt/unity"
+"#;
+
+    #[test]
+    fn test_json_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&Config::json_schema()).unwrap();
+        let properties = &schema["properties"];
+        for key in [
+            "include",
+            "rust",
+            "unity",
+            "env",
+            "project_types",
+            "profiles",
+        ] {
+            assert!(properties.get(key).is_some(), "missing {}", key);
+        }
+        assert!(properties.get("profile").is_none());
+        assert_eq!(schema["required"], serde_json::json!(["rust", "unity"]));
+        let steps = schema["definitions"]["LaunchStep"]["oneOf"]
+            .as_array()
+            .unwrap();
+        assert_eq!(steps.len(), 8);
+        assert_eq!(
+            steps[0]["properties"]["type"]["enum"],
+            serde_json::json!(["ide"])
+        );
+    }
+
+    fn write_config(dir: &Path) -> PathBuf {
+        let path = dir.join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_from_file_uses_default_profile() {
+        let temp_dir = tempdir().unwrap();
+        let path = write_config(temp_dir.path());
+
+        let config = Config::from_file(&path, None).unwrap();
+        assert_eq!(config.profile.as_deref(), Some("home"));
+        assert_eq!(config.rust.ide_path, PathBuf::from("/usr/bin/zed"));
+        assert_eq!(config.unity.editor_base_path, PathBuf::from("/opt/unity"));
+    }
+
+    #[test]
+    fn test_from_file_explicit_profile_overrides_section() {
+        let temp_dir = tempdir().unwrap();
+        let path = write_config(temp_dir.path());
+
+        let config = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(config.rust.ide_path, PathBuf::from("/usr/bin/rustrover"));
+        assert_eq!(config.unity.editor_base_path, PathBuf::from("/mnt/unity"));
+        assert_eq!(
+            config.unity.json_editor_path,
+            PathBuf::from("/usr/bin/code")
+        );
+    }
+
+    #[test]
+    fn test_from_file_unknown_profile() {
+        let temp_dir = tempdir().unwrap();
+        let path = write_config(temp_dir.path());
+
+        assert!(Config::from_file(&path, Some("laptop")).is_err());
+    }
+
+    #[test]
+    fn test_from_file_profile_replaces_launch_steps() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let config = format!(
+            "{}\n[[profiles.work.rust.launch]]\ntype = \"ide\"\n",
+            CONFIG
+        );
+        fs::write(&path, config).unwrap();
+
+        let home = Config::from_file(&path, None).unwrap();
+        assert_eq!(home.rust.launch, launch::default_rust_steps());
+        let work = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(work.rust.launch.len(), 1);
+    }
+
+    #[test]
+    fn test_from_file_merges_includes() {
+        let temp_dir = tempdir().unwrap();
+        let team = temp_dir.path().join("team.toml");
+        fs::write(
+            &team,
+            r#"
+[unity]
+editor_base_path = "/team/unity"
+install_roots = ["/team/editors"]
+
+[project_types.node]
+markers = ["package.json"]
+ide_path = "/usr/bin/code"
+"#,
+        )
+        .unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let config = format!("include = [\"team.toml\", \"missing.toml\"]\n{}", CONFIG);
+        fs::write(&path, config).unwrap();
+
+        let config = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(config.unity.editor_base_path, PathBuf::from("/mnt/unity"));
+        assert_eq!(
+            config.unity.install_roots,
+            vec![PathBuf::from("/team/editors")]
+        );
+        assert_eq!(
+            config.project_types["node"].markers,
+            vec![PathBuf::from("package.json")]
+        );
+    }
+
+    #[test]
+    fn test_default_config_keeps_included_settings() {
+        let temp_dir = tempdir().unwrap();
+        let team = temp_dir.path().join("team.toml");
+        fs::write(
+            &team,
+            r#"
+[rust]
+ide_path = "/team/rustrover"
+launch = [{ type = "ide" }]
+
+[unity]
+editor_base_path = "/team/unity"
+json_editor_path = "/team/code"
+
+[history]
+limit = 30
+"#,
+        )
+        .unwrap();
+        let path = temp_dir.path().join("config.toml");
+        Config::create_default(&path).unwrap();
+        let personal = fs::read_to_string(&path).unwrap();
+        fs::write(&path, format!("include = [\"team.toml\"]\n{}", personal)).unwrap();
+
+        let config = Config::from_file(&path, None).unwrap();
+        assert_eq!(config.rust.ide_path, PathBuf::from("/team/rustrover"));
+        assert_eq!(config.rust.launch.len(), 1);
+        assert_eq!(config.unity.editor_base_path, PathBuf::from("/team/unity"));
+        assert_eq!(config.history.limit, 30);
+    }
+
+    #[test]
+    fn test_from_file_recursive_include() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let config = format!("include = [\"config.toml\"]\n{}", CONFIG);
+        fs::write(&path, config).unwrap();
+
+        let config = Config::from_file(&path, None).unwrap();
+        assert_eq!(config.rust.ide_path, PathBuf::from("/usr/bin/zed"));
+    }
+
+    #[test]
+    fn test_from_file_include_cycle_through_other_spelling() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path().join("devenv");
+        fs::create_dir(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let config = format!("include = [\"../devenv/./config.toml\"]\n{}", CONFIG);
+        fs::write(&path, config).unwrap();
+
+        let config = Config::from_file(&path, None).unwrap();
+        assert_eq!(config.rust.ide_path, PathBuf::from("/usr/bin/zed"));
+    }
+
+    #[test]
+    fn test_from_file_diamond_includes() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        fs::write(
+            dir.join("base.toml"),
+            "[unity]\ninstall_roots = [\"/base\"]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("a.toml"), "include = [\"base.toml\"]\n").unwrap();
+        fs::write(
+            dir.join("b.toml"),
+            "include = [\"base.toml\"]\n[unity]\ninstall_roots = [\"/b\"]\n",
+        )
+        .unwrap();
+        let path = dir.join("config.toml");
+        let config = format!("include = [\"b.toml\", \"a.toml\"]\n{}", CONFIG);
+        fs::write(&path, config).unwrap();
+
+        // a.toml includes base.toml again, which is not a cycle, and overrides b.toml with it
+        let config = Config::from_file(&path, None).unwrap();
+        assert_eq!(config.unity.install_roots, vec![PathBuf::from("/base")]);
+    }
+
+    #[test]
+    fn test_from_file_os_sections() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let config = format!(
+            "{}\n[rust.{}]\nide_path = \"/native/ide\"\n[unity.{}]\neditor_base_path = \"/other\"\n[profiles.work.rust.{}]\nide_path = \"/work/ide\"\n",
+            CONFIG,
+            env::consts::OS,
+            if env::consts::OS == "windows" { "linux" } else { "windows" },
+            env::consts::OS,
+        );
+        fs::write(&path, config).unwrap();
+
+        let home = Config::from_file(&path, Some("home")).unwrap();
+        assert_eq!(home.rust.ide_path, PathBuf::from("/usr/bin/zed"));
+        assert_eq!(home.unity.editor_base_path, PathBuf::from("/opt/unity"));
+        let work = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(work.rust.ide_path, PathBuf::from("/work/ide"));
+    }
+
+    #[test]
+    fn test_resolve_os_sections() {
+        let mut table: toml::Table = format!(
+            "[rust]\nide_path = \"/generic\"\n[rust.{}]\nide_path = \"/native\"\n",
+            env::consts::OS
+        )
+        .parse()
+        .unwrap();
+        resolve_os_sections(&mut table);
+
+        let rust = table["rust"].as_table().unwrap();
+        assert_eq!(rust["ide_path"].as_str(), Some("/native"));
+        assert!(!rust.contains_key(env::consts::OS));
+    }
+
+    #[test]
+    fn test_os_names_as_profile_and_type_names() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let config = format!(
+            "{}\n[profiles.{os}.rust]\nide_path = \"/profile/ide\"\n[project_types.{os}]\nmarkers = [\"marker\"]\n",
+            CONFIG,
+            os = env::consts::OS,
+        );
+        fs::write(&path, config).unwrap();
+
+        let config = Config::from_file(&path, Some(env::consts::OS)).unwrap();
+        assert_eq!(config.rust.ide_path, PathBuf::from("/profile/ide"));
+        assert!(config.project_types.contains_key(env::consts::OS));
+    }
+
+    #[test]
+    fn test_save_to_file_writes_into_os_section() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let original = format!(
+            "{}\n[rust.{}]\nide_path = \"/native/ide\"\n",
+            CONFIG,
+            env::consts::OS
+        );
+        fs::write(&path, &original).unwrap();
+
+        let mut config = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(config.rust.ide_path, PathBuf::from("/native/ide"));
+        config.rust.ide_path = PathBuf::from("/new/ide");
+        config.unity.json_editor_path = PathBuf::from("/new/editor");
+        config.save_to_file(&path).unwrap();
+
+        let saved = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(saved.rust.ide_path, PathBuf::from("/new/ide"));
+        assert_eq!(saved.unity.json_editor_path, PathBuf::from("/new/editor"));
+    }
+
+    #[test]
+    fn test_from_file_expands_paths() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        env::set_var("DEVENV_TEST_UNITY_ROOT", "/srv/unity");
+        let config = CONFIG.replace(
+            "editor_base_path = \"/opt/unity\"",
+            "editor_base_path = \"$DEVENV_TEST_UNITY_ROOT/editors\"\ninstall_roots = [\"${DEVENV_TEST_UNITY_ROOT}/more\"]",
+        );
+        fs::write(&path, config).unwrap();
+
+        let config = Config::from_file(&path, Some("home")).unwrap();
+        assert_eq!(
+            config.unity.editor_base_path,
+            PathBuf::from("/srv/unity/editors")
+        );
+        assert_eq!(
+            config.unity.install_roots,
+            vec![PathBuf::from("/srv/unity/more")]
+        );
+    }
+
+    #[test]
+    fn test_save_to_file_preserves_formatting() {
+        let temp_dir = tempdir().unwrap();
+        let path = write_config(temp_dir.path());
+        let original = format!("# Shared settings\ncustom_key = 1 # kept\n{}", CONFIG);
+        fs::write(&path, &original).unwrap();
+
+        let mut config = Config::from_file(&path, None).unwrap();
+        config.profile = None;
+        config.unity.json_editor_path = PathBuf::from("/usr/bin/zed");
+        config.save_to_file(&path).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            saved,
+            original.replace(
+                "json_editor_path = \"/usr/bin/code\"",
+                "json_editor_path = \"/usr/bin/zed\""
+            )
+        );
+    }
+
+    #[test]
+    fn test_save_to_file_without_changes_is_untouched() {
+        let temp_dir = tempdir().unwrap();
+        let path = write_config(temp_dir.path());
+
+        let config = Config::from_file(&path, Some("work")).unwrap();
+        config.save_to_file(&path).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+    }
+
+    #[test]
+    fn test_save_to_file_writes_into_active_profile() {
+        let temp_dir = tempdir().unwrap();
+        let path = write_config(temp_dir.path());
+
+        let mut config = Config::from_file(&path, Some("work")).unwrap();
+        config.rust.ide_path = PathBuf::from("/usr/bin/code");
+        config.save_to_file(&path).unwrap();
+
+        let base = Config::from_file(&path, Some("home")).unwrap();
+        assert_eq!(base.unity.editor_base_path, PathBuf::from("/opt/unity"));
+        let work = Config::from_file(&path, Some("work")).unwrap();
+        assert_eq!(work.rust.ide_path, PathBuf::from("/usr/bin/code"));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::config::CustomProjectConfig;
+use crate::environment::Environment;
+use crate::launch::{self, StepKind};
+use crate::project_settings::ProjectSettings;
+use crate::template::ProjectVariables;
+use crate::utils;
+use std::path::Path;
+use std::process::Child;
+
+/// Runs the launch steps of a custom project type and returns the IDE process if one was
+/// started.
+pub fn open_custom_project(
+    name: &str,
+    config: &CustomProjectConfig,
+    project_path: &Path,
+    settings: &ProjectSettings,
+    environment: &Environment,
+) -> Option<Child> {
+    println!("Opening {} project: {}", name, project_path.display());
+    let variables = ProjectVariables::new(project_path);
+    let mut ide = None;
+    for step in launch::enabled_steps(&config.launch, settings) {
+        match &step.kind {
+            StepKind::Ide { .. } if config.ide_path.as_os_str().is_empty() => {
+                eprintln!("No ide_path configured for project type '{}'.", name)
+            }
+            StepKind::Ide { args } => match variables.expand_args(args) {
+                Ok(args) => {
+                    ide = utils::open_in_ide(&config.ide_path, project_path, &args, environment)
+                        .or(ide);
+                }
+                Err(e) => eprintln!("Skipping launch step 'ide': {}", e),
+            },
+            kind => launch::run_common_step(kind, project_path, &variables, environment),
+        }
+    }
+    ide
+}
@@ -0,0 +1,210 @@
+use crate::config::Config;
+use crate::project_settings::{Activation, ProjectSettings};
+use crate::project_type::ProjectType;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+/// Environment variables set on every process launched for a project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Environment {
+    vars: BTreeMap<String, String>,
+    removed: BTreeSet<String>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    /// Collects the variables for a project, from the least to the most specific source:
+    /// the global `env`, the project type's `env`, the activated shell environment, the
+    /// project's `env_file` and its `env`.
+    pub fn for_project(
+        config: &Config,
+        project_type: &ProjectType,
+        project_dir: &Path,
+        settings: &ProjectSettings,
+    ) -> Self {
+        let mut environment = Environment::new();
+        environment.extend(&config.env);
+        match project_type {
+            ProjectType::Unity => environment.extend(&config.unity.env),
+            ProjectType::Rust => environment.extend(&config.rust.env),
+            ProjectType::Custom(name) => {
+                if let Some(custom) = config.project_types.get(name) {
+                    environment.extend(&custom.env);
+                }
+            }
+        }
+        if let Some(activation) = settings.activate {
+            if let Err(e) = environment.activate(activation, project_dir) {
+                eprintln!("Failed to activate the {:?} environment: {}", activation, e);
+            }
+        }
+        if let Some(env_file) = &settings.env_file {
+            let path = project_dir.join(env_file);
+            if let Err(e) = environment.load_dotenv(&path) {
+                eprintln!("Failed to load {}: {}", path.display(), e);
+            }
+        }
+        environment.extend(&settings.env);
+        environment
+    }
+
+    /// Adds `vars`, overriding variables that are already set.
+    pub fn extend(&mut self, vars: &BTreeMap<String, String>) {
+        for (key, value) in vars {
+            self.set(key.clone(), value.clone());
+        }
+    }
+
+    fn set(&mut self, key: String, value: String) {
+        self.removed.remove(&key);
+        self.vars.insert(key, value);
+    }
+
+    fn remove(&mut self, key: String) {
+        self.vars.remove(&key);
+        self.removed.insert(key);
+    }
+
+    /// Evaluates the project's direnv or nix-shell environment and adds the variables it
+    /// changes. The variables collected so far are visible to the evaluation.
+    pub fn activate(
+        &mut self,
+        activation: Activation,
+        project_dir: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut command = match activation {
+            Activation::Direnv => {
+                let mut command = Command::new("direnv");
+                command.args(["export", "json"]);
+                command
+            }
+            Activation::Nix => {
+                let mut command = Command::new("nix-shell");
+                command.args(["--run", "env -0"]);
+                command
+            }
+        };
+        let output = self.apply(&mut command).current_dir(project_dir).output()?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr)
+                .trim()
+                .to_string()
+                .into());
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        match activation {
+            Activation::Direnv => {
+                for (key, value) in parse_direnv_export(&stdout)? {
+                    match value {
+                        Some(value) => self.set(key, value),
+                        None => self.remove(key),
+                    }
+                }
+            }
+            Activation::Nix => {
+                let current: BTreeMap<String, String> = env::vars().collect();
+                for (key, value) in parse_env_list(&stdout) {
+                    let unchanged =
+                        current.get(&key) == Some(&value) && !self.vars.contains_key(&key);
+                    if !unchanged {
+                        self.set(key, value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the variables of a `.env` file, overriding variables that are already set.
+    pub fn load_dotenv(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        for item in dotenvy::from_path_iter(path)? {
+            let (key, value) = item?;
+            self.vars.insert(key, value);
+        }
+        Ok(())
+    }
+
+    pub fn apply<'a>(&self, command: &'a mut Command) -> &'a mut Command {
+        for key in &self.removed {
+            command.env_remove(key);
+        }
+        command.envs(&self.vars)
+    }
+}
+
+/// Parses the output of `direnv export json`, where `null` marks a variable to unset. direnv
+/// prints nothing when the environment doesn't change.
+fn parse_direnv_export(output: &str) -> Result<BTreeMap<String, Option<String>>, Box<dyn Error>> {
+    if output.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(output)?)
+}
+
+/// Parses the NUL separated `KEY=value` list printed by `env -0`.
+fn parse_env_list(output: &str) -> Vec<(String, String)> {
+    output
+        .split('\0')
+        .filter_map(|entry| entry.split_once('='))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_later_sources_override_earlier_ones() {
+        let temp_dir = tempdir().unwrap();
+        let dotenv = temp_dir.path().join(".env");
+        fs::write(
+            &dotenv,
+            "CACHE_SERVER=cache.local\nLICENSE=\"from dotenv\"\n",
+        )
+        .unwrap();
+
+        let mut global = BTreeMap::new();
+        global.insert("LICENSE".to_string(), "global".to_string());
+        global.insert("EDITOR".to_string(), "vim".to_string());
+        let mut project = BTreeMap::new();
+        project.insert("CACHE_SERVER".to_string(), "cache.project".to_string());
+
+        let mut environment = Environment::new();
+        environment.extend(&global);
+        environment.load_dotenv(&dotenv).unwrap();
+        environment.extend(&project);
+
+        assert_eq!(environment.vars["EDITOR"], "vim");
+        assert_eq!(environment.vars["LICENSE"], "from dotenv");
+        assert_eq!(environment.vars["CACHE_SERVER"], "cache.project");
+    }
+
+    #[test]
+    fn test_parse_activation_output() {
+        let direnv =
+            parse_direnv_export(r#"{"CARGO_HOME": "/nix/cargo", "OLD_VAR": null}"#).unwrap();
+        assert_eq!(direnv["CARGO_HOME"], Some("/nix/cargo".to_string()));
+        assert_eq!(direnv["OLD_VAR"], None);
+        assert!(parse_direnv_export("").unwrap().is_empty());
+
+        let nix = parse_env_list("PATH=/nix/bin:/usr/bin\0SHELL_HOOK=a=b\nc\0");
+        assert_eq!(
+            nix,
+            vec![
+                ("PATH".to_string(), "/nix/bin:/usr/bin".to_string()),
+                ("SHELL_HOOK".to_string(), "a=b\nc".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use crate::recent_projects::unix_timestamp;
+use crate::utils;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Append-only log of opened projects, one JSON event per line
+const JOURNAL_FILE: &str = "journal.jsonl";
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    /// A project was opened
+    Open {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<String>,
+        timestamp: u64,
+    },
+    /// The IDE of a project opened with `--wait` was closed after `duration` seconds
+    Session {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project_type: Option<String>,
+        started: u64,
+        duration: u64,
+    },
+}
+
+impl JournalEvent {
+    pub fn open(path: &Path, project_type: Option<String>, profile: Option<String>) -> Self {
+        JournalEvent::Open {
+            path: path.to_path_buf(),
+            project_type,
+            profile,
+            timestamp: unix_timestamp(),
+        }
+    }
+
+    /// A session that started at `started` and ends now.
+    pub fn session(path: &Path, project_type: Option<String>, started: u64) -> Self {
+        JournalEvent::Session {
+            path: path.to_path_buf(),
+            project_type,
+            started,
+            duration: unix_timestamp().saturating_sub(started),
+        }
+    }
+}
+
+/// Appends `event` to the journal in `config_dir`.
+pub fn append(config_dir: &Path, event: &JournalEvent) -> Result<(), Box<dyn Error>> {
+    let path = config_dir.join(JOURNAL_FILE);
+    let _lock = utils::lock_file(&path)?;
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Reads the journal in `config_dir`, skipping lines that can't be parsed.
+pub fn load(config_dir: &Path) -> Result<Vec<JournalEvent>, Box<dyn Error>> {
+    let path = config_dir.join(JOURNAL_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Number of opens and seconds spent in tracked sessions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub opens: u32,
+    pub seconds: u64,
+}
+
+/// Usage per project, per project type and per week, the weeks keyed by the day their
+/// Monday falls on, counted from the Unix epoch.
+#[derive(Debug, Default, PartialEq)]
+pub struct Stats {
+    pub projects: BTreeMap<PathBuf, Usage>,
+    pub types: BTreeMap<String, Usage>,
+    pub weeks: BTreeMap<u64, Usage>,
+}
+
+impl Stats {
+    /// Adds up the events that happened at or after `since`.
+    pub fn from_events(events: &[JournalEvent], since: u64) -> Self {
+        let mut stats = Stats::default();
+        for event in events {
+            let (path, project_type, time, usage) = match event {
+                JournalEvent::Open {
+                    path,
+                    project_type,
+                    timestamp,
+                    ..
+                } => (
+                    path,
+                    project_type,
+                    *timestamp,
+                    Usage {
+                        opens: 1,
+                        seconds: 0,
+                    },
+                ),
+                JournalEvent::Session {
+                    path,
+                    project_type,
+                    started,
+                    duration,
+                } => (
+                    path,
+                    project_type,
+                    *started,
+                    Usage {
+                        opens: 0,
+                        seconds: *duration,
+                    },
+                ),
+            };
+            if time < since {
+                continue;
+            }
+            let project_type = project_type
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            stats.projects.entry(path.clone()).or_default().add(usage);
+            stats.types.entry(project_type).or_default().add(usage);
+            stats.weeks.entry(week_start(time)).or_default().add(usage);
+        }
+        stats
+    }
+
+    /// Prints the tables, naming projects with `name`. Time spent is only shown when
+    /// sessions were tracked.
+    pub fn print(&self, name: impl Fn(&Path) -> String) {
+        if self.projects.is_empty() {
+            println!("No projects were opened in this period.");
+            return;
+        }
+        let tracked = self.projects.values().any(|usage| usage.seconds > 0);
+
+        let mut projects: Vec<(String, Usage)> = self
+            .projects
+            .iter()
+            .map(|(path, usage)| (name(path), *usage))
+            .collect();
+        projects.sort_by(|a, b| b.1.opens.cmp(&a.1.opens).then(a.0.cmp(&b.0)));
+        print_table("Project", &projects, tracked);
+
+        let mut types: Vec<(String, Usage)> = self
+            .types
+            .iter()
+            .map(|(t, usage)| (t.clone(), *usage))
+            .collect();
+        types.sort_by(|a, b| b.1.opens.cmp(&a.1.opens).then(a.0.cmp(&b.0)));
+        println!();
+        print_table("Type", &types, tracked);
+
+        let weeks: Vec<(String, Usage)> = self
+            .weeks
+            .iter()
+            .map(|(day, usage)| (format_date(*day), *usage))
+            .collect();
+        println!();
+        print_table("Week of", &weeks, tracked);
+    }
+}
+
+impl Usage {
+    fn add(&mut self, other: Usage) {
+        self.opens += other.opens;
+        self.seconds += other.seconds;
+    }
+}
+
+fn print_table(heading: &str, rows: &[(String, Usage)], tracked: bool) {
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.len())
+        .chain([heading.len()])
+        .max()
+        .unwrap_or(0);
+    if tracked {
+        println!("{:<width$}  {:>5}  {:>8}", heading, "Opens", "Time");
+    } else {
+        println!("{:<width$}  {:>5}", heading, "Opens");
+    }
+    for (label, usage) in rows {
+        if tracked {
+            let time = format_duration(usage.seconds);
+            println!("{:<width$}  {:>5}  {:>8}", label, usage.opens, time);
+        } else {
+            println!("{:<width$}  {:>5}", label, usage.opens);
+        }
+    }
+}
+
+/// Day, counted from the Unix epoch, of the Monday of the week `timestamp` falls in.
+fn week_start(timestamp: u64) -> u64 {
+    let day = timestamp / SECONDS_PER_DAY;
+    // 1970-01-01 was a Thursday, the days before the first Monday count from the epoch itself
+    day.saturating_sub((day + 3) % 7)
+}
+
+/// Formats a day counted from the Unix epoch as `YYYY-MM-DD`.
+fn format_date(day: u64) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats a duration in seconds as e.g. "3h 20m".
+fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    match minutes {
+        0 if seconds == 0 => "-".to_string(),
+        0..=59 => format!("{}m", minutes),
+        _ => format!("{}h {}m", minutes / 60, minutes % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn open(path: &str, project_type: &str, timestamp: u64) -> JournalEvent {
+        JournalEvent::Open {
+            path: PathBuf::from(path),
+            project_type: Some(project_type.to_string()),
+            profile: None,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        assert!(load(config_dir).unwrap().is_empty());
+
+        let event = open("/game", "unity", 100);
+        append(config_dir, &event).unwrap();
+        fs::write(
+            config_dir.join(JOURNAL_FILE),
+            fs::read_to_string(config_dir.join(JOURNAL_FILE)).unwrap() + "not json\n",
+        )
+        .unwrap();
+        let session = JournalEvent::Session {
+            path: PathBuf::from("/game"),
+            project_type: None,
+            started: 100,
+            duration: 60,
+        };
+        append(config_dir, &session).unwrap();
+
+        assert_eq!(load(config_dir).unwrap(), vec![event, session]);
+    }
+
+    #[test]
+    fn test_stats() {
+        // 2024-01-01 was a Monday
+        let monday = 19_723 * SECONDS_PER_DAY;
+        let events = vec![
+            open("/old", "rust", monday - 8 * SECONDS_PER_DAY),
+            open("/game", "unity", monday + 3600),
+            JournalEvent::Session {
+                path: PathBuf::from("/game"),
+                project_type: Some("unity".to_string()),
+                started: monday + 3600,
+                duration: 5400,
+            },
+            open("/game", "unity", monday + 6 * SECONDS_PER_DAY),
+            open("/tool", "rust", monday + 7 * SECONDS_PER_DAY),
+        ];
+
+        let stats = Stats::from_events(&events, monday);
+        assert_eq!(
+            stats.projects[Path::new("/game")],
+            Usage {
+                opens: 2,
+                seconds: 5400
+            }
+        );
+        assert!(!stats.projects.contains_key(Path::new("/old")));
+        assert_eq!(stats.types["rust"].opens, 1);
+        assert_eq!(stats.types["unity"].opens, 2);
+        let weeks: Vec<(String, u32)> = stats
+            .weeks
+            .iter()
+            .map(|(day, usage)| (format_date(*day), usage.opens))
+            .collect();
+        assert_eq!(
+            weeks,
+            vec![("2024-01-01".to_string(), 2), ("2024-01-08".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_format_date_and_duration() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(19_782), "2024-02-29");
+        assert_eq!(format_duration(0), "-");
+        assert_eq!(format_duration(59), "0m");
+        assert_eq!(format_duration(12_000), "3h 20m");
+    }
+
+    #[test]
+    fn test_week_start() {
+        assert_eq!(week_start(0), 0);
+        assert_eq!(week_start(3 * SECONDS_PER_DAY + 60), 0);
+        assert_eq!(week_start(4 * SECONDS_PER_DAY), 4);
+        // 2024-01-07 was a Sunday
+        assert_eq!(
+            format_date(week_start(19_729 * SECONDS_PER_DAY)),
+            "2024-01-01"
+        );
+    }
+}
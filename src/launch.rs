@@ -0,0 +1,306 @@
+use crate::environment::Environment;
+use crate::project_settings::ProjectSettings;
+use crate::template::{Arguments, ProjectVariables};
+use crate::utils;
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LaunchStep {
+    #[serde(flatten)]
+    pub kind: StepKind,
+    /// Identifier used to enable or disable the step per project, defaults to the step type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepKind {
+    /// Opens the project in the IDE or editor of its project type
+    Ide {
+        #[serde(default, skip_serializing_if = "Arguments::is_empty")]
+        args: Arguments,
+    },
+    /// Opens the first `.sln` file in the project directory
+    Solution,
+    /// Opens lazygit for a directory relative to the project, or the project itself
+    GitUi {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
+    /// Opens lazygit for every embedded Unity package
+    PackageGitUi,
+    /// Opens a directory relative to the project in the file manager
+    FileManager {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
+    /// Opens a file relative to the project with its default application
+    OpenFile { path: PathBuf },
+    /// Opens the Unity package manifests in the configured json editor
+    PackageManifests,
+    /// Runs an arbitrary program from the project directory
+    Command {
+        program: PathBuf,
+        #[serde(default, skip_serializing_if = "Arguments::is_empty")]
+        args: Arguments,
+    },
+}
+
+impl StepKind {
+    /// Mutable references to every path the step refers to.
+    pub fn paths_mut(&mut self) -> Vec<&mut PathBuf> {
+        match self {
+            StepKind::GitUi { path } | StepKind::FileManager { path } => path.iter_mut().collect(),
+            StepKind::OpenFile { path } => vec![path],
+            StepKind::Command { program, .. } => vec![program],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            StepKind::Ide { .. } => "ide",
+            StepKind::Solution => "solution",
+            StepKind::GitUi { .. } => "git_ui",
+            StepKind::PackageGitUi => "package_git_ui",
+            StepKind::FileManager { .. } => "file_manager",
+            StepKind::OpenFile { .. } => "open_file",
+            StepKind::PackageManifests => "package_manifests",
+            StepKind::Command { .. } => "command",
+        }
+    }
+}
+
+impl LaunchStep {
+    pub fn new(kind: StepKind) -> Self {
+        LaunchStep {
+            kind,
+            name: None,
+            enabled: true,
+        }
+    }
+
+    pub fn named(name: &str, kind: StepKind) -> Self {
+        LaunchStep {
+            name: Some(name.to_string()),
+            ..LaunchStep::new(kind)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.kind.type_name())
+    }
+
+    /// Whether the step should run, taking the project's own settings into account.
+    pub fn is_enabled(&self, settings: &ProjectSettings) -> bool {
+        let name = self.name();
+        if settings.launch.disabled.iter().any(|n| n == name) {
+            false
+        } else {
+            self.enabled || settings.launch.enabled.iter().any(|n| n == name)
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn default_rust_steps() -> Vec<LaunchStep> {
+    vec![
+        LaunchStep::new(StepKind::Ide {
+            args: Arguments::default(),
+        }),
+        LaunchStep::new(StepKind::GitUi { path: None }),
+    ]
+}
+
+pub fn default_custom_steps() -> Vec<LaunchStep> {
+    default_rust_steps()
+}
+
+pub fn default_unity_steps() -> Vec<LaunchStep> {
+    vec![
+        LaunchStep::new(StepKind::Ide {
+            args: Arguments::default(),
+        }),
+        LaunchStep::new(StepKind::Solution),
+        LaunchStep::new(StepKind::GitUi { path: None }),
+        LaunchStep::named(
+            "packages_explorer",
+            StepKind::FileManager {
+                path: Some(PathBuf::from("Packages")),
+            },
+        ),
+        LaunchStep::new(StepKind::PackageGitUi),
+        LaunchStep::new(StepKind::PackageManifests),
+    ]
+}
+
+/// Returns the steps that should run for a project, in their configured order.
+pub fn enabled_steps<'a>(
+    steps: &'a [LaunchStep],
+    settings: &'a ProjectSettings,
+) -> impl Iterator<Item = &'a LaunchStep> {
+    steps.iter().filter(move |step| step.is_enabled(settings))
+}
+
+/// Runs a step that behaves the same for every project type. Placeholders in its paths
+/// and arguments are expanded first, and the step is skipped if that fails.
+pub fn run_common_step(
+    kind: &StepKind,
+    project_path: &Path,
+    variables: &ProjectVariables,
+    environment: &Environment,
+) {
+    if let Err(e) = try_run_common_step(kind, project_path, variables, environment) {
+        eprintln!("Skipping launch step '{}': {}", kind.type_name(), e);
+    }
+}
+
+fn try_run_common_step(
+    kind: &StepKind,
+    project_path: &Path,
+    variables: &ProjectVariables,
+    environment: &Environment,
+) -> Result<(), String> {
+    match kind {
+        StepKind::GitUi { path } => {
+            utils::open_lazygit(&resolve(project_path, path, variables)?, environment)
+        }
+        StepKind::FileManager { path } => {
+            utils::open_directory(&resolve(project_path, path, variables)?, environment)
+        }
+        StepKind::OpenFile { path } => utils::open_file(
+            &project_path.join(variables.expand_path(path)?),
+            environment,
+        ),
+        StepKind::Command { program, args } => run_command(
+            &variables.expand_path(program)?,
+            &variables.expand_args(args)?,
+            project_path,
+            environment,
+        ),
+        _ => return Err("not supported for this project type".to_string()),
+    }
+    Ok(())
+}
+
+fn resolve(
+    project_path: &Path,
+    path: &Option<PathBuf>,
+    variables: &ProjectVariables,
+) -> Result<PathBuf, String> {
+    match path {
+        Some(path) => Ok(project_path.join(variables.expand_path(path)?)),
+        None => Ok(project_path.to_path_buf()),
+    }
+}
+
+fn run_command(program: &Path, args: &[String], project_path: &Path, environment: &Environment) {
+    let result = environment
+        .apply(&mut Command::new(program))
+        .args(args)
+        .current_dir(project_path)
+        .spawn();
+    match result {
+        Ok(_) => println!("Started {}", program.display()),
+        Err(e) => eprintln!("Failed to start {}. Error: {}", program.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_settings::LaunchOverrides;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Steps {
+        launch: Vec<LaunchStep>,
+    }
+
+    #[test]
+    fn test_deserialize_steps() {
+        let steps: Steps = toml::from_str(
+            r#"
+[[launch]]
+type = "ide"
+
+[[launch]]
+type = "file_manager"
+name = "packages_explorer"
+path = "Packages"
+enabled = false
+
+[[launch]]
+type = "command"
+program = "cargo"
+args = ["build"]
+
+[[launch]]
+type = "ide"
+args = "-logFile {project_dir}/Logs/editor.log"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            steps.launch[0],
+            LaunchStep::new(StepKind::Ide {
+                args: Arguments::default()
+            })
+        );
+        assert_eq!(steps.launch[1].name(), "packages_explorer");
+        assert!(!steps.launch[1].enabled);
+        assert_eq!(
+            steps.launch[2].kind,
+            StepKind::Command {
+                program: PathBuf::from("cargo"),
+                args: Arguments::List(vec!["build".to_string()]),
+            }
+        );
+        assert_eq!(
+            steps.launch[3].kind,
+            StepKind::Ide {
+                args: Arguments::Line("-logFile {project_dir}/Logs/editor.log".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_default_steps_round_trip() {
+        let steps = Steps {
+            launch: default_unity_steps(),
+        };
+        let toml = toml::to_string(&steps).unwrap();
+        let parsed: Steps = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed.launch, default_unity_steps());
+    }
+
+    #[test]
+    fn test_enabled_steps_respects_project_settings() {
+        let mut steps = default_rust_steps();
+        steps.push(LaunchStep {
+            enabled: false,
+            ..LaunchStep::named("build", StepKind::Solution)
+        });
+        let settings = ProjectSettings {
+            launch: LaunchOverrides {
+                enabled: vec!["build".to_string()],
+                disabled: vec!["git_ui".to_string()],
+            },
+            ..ProjectSettings::default()
+        };
+
+        let names: Vec<&str> = enabled_steps(&steps, &settings)
+            .map(|step| step.name())
+            .collect();
+        assert_eq!(names, vec!["ide", "build"]);
+    }
+}
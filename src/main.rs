@@ -1,20 +1,36 @@
-use crate::cli::{Cli, Command};
-use crate::config::Config;
+use crate::cli::{Cli, Command, ConfigCommand, HistoryCommand};
+use crate::config::{Config, HistoryConfig};
+use crate::custom_project::open_custom_project;
+use crate::environment::Environment;
+use crate::journal::{JournalEvent, Stats};
+use crate::project_settings::ProjectSettings;
 use crate::project_type::ProjectType;
-use crate::recent_projects::RecentProjects;
+use crate::recent_projects::{
+    PathMapping, ProjectDetails, ProjectFilter, RecentProjects, SortOrder,
+};
 use crate::rust::open_rust_project;
 use crate::unity::open_unity_project;
-use crate::utils::prompt_user_for_path;
-use std::env;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Confirm;
 use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::{env, fs};
 use structopt::StructOpt;
 
 mod cli;
 mod config;
+mod custom_project;
+mod environment;
+mod journal;
+mod launch;
+mod project_settings;
 mod project_type;
 mod recent_projects;
 mod rust;
+mod setup;
+mod template;
 mod unity;
+mod unity_editors;
 mod utils;
 
 const APP_NAME: &str = "dev_environment_launcher";
@@ -23,7 +39,14 @@ fn main() {
     let config_dir = Config::get_config_dir(APP_NAME);
     let config_path = config_dir.join("config.toml");
 
-    let mut config = match Config::get_config(&config_dir, &config_path) {
+    let args = Cli::from_args();
+    if let Some(Command::Config(ConfigCommand::Schema)) = args.command {
+        println!("{}", Config::json_schema());
+        return;
+    }
+
+    let first_run = !config_path.exists();
+    let mut config = match Config::get_config(&config_dir, &config_path, args.profile.as_deref()) {
         Ok(config) => config,
         Err(e) => {
             eprintln!("{}", e);
@@ -31,7 +54,10 @@ fn main() {
         }
     };
 
-    let args = Cli::from_args();
+    if first_run && !matches!(args.command, Some(Command::Setup)) {
+        run_setup(&mut config, &config_path);
+    }
+
     let mut recent_projects = match RecentProjects::load(&config_dir) {
         Ok(recent_projects) => recent_projects,
         Err(e) => {
@@ -39,61 +65,205 @@ fn main() {
             return;
         }
     };
+    // Not undoable and skipped for `undo`, so the change to undo stays the last one recorded
+    if config.history.auto_prune
+        && !matches!(args.command, Some(Command::Undo))
+        && !recent_projects.missing_projects().is_empty()
+    {
+        let pruned = update_recent_projects(&config_dir, &config.history, RecentProjects::prune);
+        eprintln!("Removed {} missing recent projects.", pruned.len());
+        // Indices shown and resolved from here on must match the pruned list
+        recent_projects = match RecentProjects::load(&config_dir) {
+            Ok(recent_projects) => recent_projects,
+            Err(e) => {
+                eprintln!("Failed to load recent projects: {}", e);
+                return;
+            }
+        };
+    }
 
     match args.command {
-        Some(Command::Path { path }) => open_project(
+        Some(Command::Path { path, tags }) => open_project(
             path,
+            &tags,
+            args.wait,
             &mut config,
             &config_path,
-            &mut recent_projects,
             &config_dir,
         ),
-        Some(Command::Open { index }) => open_recent_project(
-            index,
-            &mut recent_projects,
+        Some(Command::Open { project }) => open_recent_project(
+            &project,
+            &recent_projects,
+            args.wait,
             &mut config,
             &config_path,
             &config_dir,
         ),
-        Some(Command::Remove { index }) => remove_project(index, &mut recent_projects, &config_dir),
-        Some(Command::Clear) => clear_recent_projects(&mut recent_projects, &config_dir),
-        Some(Command::Options) => recent_projects.list_projects(),
-        Some(Command::Recent) => {
-            open_interactive_project(&mut recent_projects, &mut config, &config_path, &config_dir)
+        Some(Command::Remove { project }) => remove_project(&project, &config_dir, &config.history),
+        Some(Command::Clear { all, yes }) => {
+            clear_recent_projects(all, yes, &config_dir, &config.history)
+        }
+        Some(Command::Undo) => undo(&config_dir, &config.history),
+        Some(Command::Pin { project }) => pin_project(&project, true, &config_dir, &config.history),
+        Some(Command::Unpin { project }) => {
+            pin_project(&project, false, &config_dir, &config.history)
         }
-        None => {
-            open_current_directory(&mut config, &config_path, &mut recent_projects, &config_dir)
+        Some(Command::Alias { project, name }) => {
+            alias_project(&project, name, &config_dir, &config.history)
         }
+        Some(Command::Prune { dry_run }) => {
+            prune_projects(dry_run, &recent_projects, &config_dir, &config.history)
+        }
+        Some(Command::Tag {
+            project,
+            tags,
+            remove,
+        }) => tag_project(&project, &tags, remove, &config_dir, &config.history),
+        Some(Command::Options { sort, long, filter }) => {
+            recent_projects.list_projects(sort, &filter.into(), long)
+        }
+        Some(Command::Recent { sort, filter }) => open_interactive_project(
+            sort,
+            &filter.into(),
+            &recent_projects,
+            args.wait,
+            &mut config,
+            &config_path,
+            &config_dir,
+        ),
+        Some(Command::Stats { weeks }) => show_stats(weeks, &recent_projects, &config_dir),
+        Some(Command::History(HistoryCommand::Export)) => export_history(&recent_projects),
+        Some(Command::History(HistoryCommand::Import { file, mappings })) => {
+            import_history(&file, &mappings, &config_dir, &config.history)
+        }
+        Some(Command::Setup) => run_setup(&mut config, &config_path),
+        Some(Command::Config(ConfigCommand::Schema)) => unreachable!(),
+        None => open_current_directory(args.wait, &mut config, &config_path, &config_dir),
     }
 }
 
 fn open_project(
     project_dir: PathBuf,
+    tags: &[String],
+    wait: bool,
     config: &mut Config,
     config_path: &Path,
-    recent_projects: &mut RecentProjects,
     config_dir: &Path,
 ) {
     if !project_dir.is_dir() {
         eprintln!("Provided path is not a directory.");
         return;
     }
+    let project_dir = utils::canonical_path(&project_dir);
 
-    match ProjectType::from_path(&project_dir) {
+    let settings = ProjectSettings::load(&project_dir).unwrap_or_else(|e| {
+        eprintln!("Failed to load project settings: {}", e);
+        ProjectSettings::default()
+    });
+
+    match ProjectType::from_path(&project_dir, &config.project_types) {
         Some(project_type) => {
             println!("Project type: {:?}", &project_type);
-            match project_type {
-                ProjectType::Unity => open_unity(config, config_path, &project_dir),
-                ProjectType::Rust => open_rust(config, config_path, &project_dir),
+            let environment =
+                Environment::for_project(config, &project_type, &project_dir, &settings);
+            let ide = match &project_type {
+                ProjectType::Unity => {
+                    open_unity(config, config_path, &project_dir, &settings, &environment)
+                }
+                ProjectType::Rust => {
+                    open_rust(config, config_path, &project_dir, &settings, &environment)
+                }
+                ProjectType::Custom(name) => open_custom_project(
+                    name,
+                    &config.project_types[name],
+                    &project_dir,
+                    &settings,
+                    &environment,
+                ),
+            };
+            let details = ProjectDetails {
+                project_type: Some(project_type.to_string()),
+                unity_version: match project_type {
+                    ProjectType::Unity => unity::get_unity_version(&project_dir),
+                    _ => None,
+                },
+                git_branch: utils::git_branch(&project_dir),
+            };
+            update_recent_projects(config_dir, &config.history, |recent_projects| {
+                recent_projects.add_project(project_dir.clone(), config.profile.clone(), details);
+                if let Some(index) = recent_projects.position(&project_dir) {
+                    recent_projects.add_tags(index, tags);
+                }
+            });
+
+            let project_type = Some(project_type.to_string());
+            let event =
+                JournalEvent::open(&project_dir, project_type.clone(), config.profile.clone());
+            record_event(config_dir, &event);
+            if wait {
+                track_session(ide, &project_dir, project_type, config_dir);
             }
-            recent_projects.add_project(project_dir);
-            save_recent_projects(config_dir, recent_projects);
         }
         None => eprintln!("Project type not recognized."),
     }
 }
 
-fn open_unity(config: &mut Config, config_path: &Path, project_dir: &Path) {
+/// Waits for the IDE to close and records how long it was open.
+fn track_session(
+    ide: Option<Child>,
+    project_dir: &Path,
+    project_type: Option<String>,
+    config_dir: &Path,
+) {
+    let Some(mut ide) = ide else {
+        eprintln!("No IDE was started, the session is not tracked.");
+        return;
+    };
+    println!("Waiting for the IDE to close...");
+    let started = recent_projects::unix_timestamp();
+    if let Err(e) = ide.wait() {
+        eprintln!("Failed to wait for the IDE: {}", e);
+        return;
+    }
+    let event = JournalEvent::session(project_dir, project_type, started);
+    record_event(config_dir, &event);
+}
+
+fn record_event(config_dir: &Path, event: &JournalEvent) {
+    if let Err(e) = journal::append(config_dir, event) {
+        eprintln!("Failed to write to the journal: {}", e);
+    }
+}
+
+fn show_stats(weeks: Option<u64>, recent_projects: &RecentProjects, config_dir: &Path) {
+    let events = match journal::load(config_dir) {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("Failed to read the journal: {}", e);
+            return;
+        }
+    };
+    let since = weeks.map_or(0, |weeks| {
+        recent_projects::unix_timestamp().saturating_sub(weeks * 7 * 86_400)
+    });
+    Stats::from_events(&events, since).print(|path| {
+        let project = recent_projects
+            .position(path)
+            .and_then(|index| recent_projects.get_project(index));
+        match project {
+            Some(project) => project.name().to_string(),
+            None => path.display().to_string(),
+        }
+    });
+}
+
+fn open_unity(
+    config: &mut Config,
+    config_path: &Path,
+    project_dir: &Path,
+    settings: &ProjectSettings,
+    environment: &Environment,
+) -> Option<Child> {
     if config
         .unity
         .editor_base_path
@@ -101,8 +271,10 @@ fn open_unity(config: &mut Config, config_path: &Path, project_dir: &Path) {
         .unwrap_or("")
         .is_empty()
     {
-        config.unity.editor_base_path = prompt_user_for_path("Enter the Unity editor base path: ");
-        save_config(config_path, config);
+        if let Some(path) = setup::prompt_unity_editor_base_path(&config.unity.editor_base_path) {
+            config.unity.editor_base_path = path;
+            save_config(config_path, config);
+        }
     }
     if config
         .unity
@@ -111,84 +283,258 @@ fn open_unity(config: &mut Config, config_path: &Path, project_dir: &Path) {
         .unwrap_or("")
         .is_empty()
     {
-        config.unity.editor_base_path = prompt_user_for_path("Enter the json editor base path: ");
-        save_config(config_path, config);
+        if let Some(path) = setup::prompt_json_editor_path(&config.unity.json_editor_path) {
+            config.unity.json_editor_path = path;
+            save_config(config_path, config);
+        }
     }
-    open_unity_project(
-        config.unity.editor_base_path.clone(),
-        project_dir,
-        &config.unity.json_editor_path,
-    );
+    open_unity_project(&config.unity, project_dir, settings, environment)
 }
 
-fn open_rust(config: &mut Config, config_path: &Path, project_dir: &Path) {
+fn open_rust(
+    config: &mut Config,
+    config_path: &Path,
+    project_dir: &Path,
+    settings: &ProjectSettings,
+    environment: &Environment,
+) -> Option<Child> {
     if config.rust.ide_path.to_str().unwrap_or("").is_empty() {
-        config.rust.ide_path = prompt_user_for_path("Enter the Rust IDE path: ");
-        save_config(config_path, config);
+        if let Some(path) = setup::prompt_ide_path(&config.rust.ide_path) {
+            config.rust.ide_path = path;
+            save_config(config_path, config);
+        }
     }
-    open_rust_project(&config.rust.ide_path, project_dir);
+    open_rust_project(&config.rust, project_dir, settings, environment)
 }
 
 fn open_recent_project(
-    index: usize,
-    recent_projects: &mut RecentProjects,
+    selector: &str,
+    recent_projects: &RecentProjects,
+    wait: bool,
     config: &mut Config,
     config_path: &Path,
     config_dir: &Path,
 ) {
+    let index = match recent_projects.select_project(selector) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
     if let Some(project) = recent_projects.get_project(index) {
-        open_project(
-            project.clone(),
-            config,
-            config_path,
-            recent_projects,
-            config_dir,
-        );
+        if project.is_missing() {
+            eprintln!(
+                "{} no longer exists. Run `devenv prune` to remove missing projects.",
+                project.path.display()
+            );
+            return;
+        }
+        let path = project.path.clone();
+        open_project(path, &[], wait, config, config_path, config_dir);
+    }
+}
+
+fn remove_project(selector: &str, config_dir: &Path, history: &HistoryConfig) {
+    update_recent_projects_undoable(config_dir, history, |recent_projects| {
+        let removed = recent_projects
+            .find_project(selector)
+            .map(|index| recent_projects.remove_project(index));
+        match removed {
+            Ok(Some(project)) => {
+                println!("Removed {} from recent projects", project.path.display())
+            }
+            Ok(None) => eprintln!("Invalid recent project index."),
+            Err(e) => eprintln!("{}", e),
+        }
+    });
+}
+
+fn clear_recent_projects(all: bool, yes: bool, config_dir: &Path, history: &HistoryConfig) {
+    let message = if all {
+        "Cleared all recent projects."
     } else {
-        eprintln!("Invalid recent project index.");
+        "Cleared all recent projects except the pinned ones."
+    };
+    if !yes {
+        let prompt = if all {
+            "Clear all recent projects?"
+        } else {
+            "Clear all recent projects except the pinned ones?"
+        };
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Nothing was cleared, pass --yes to clear without confirmation.");
+            return;
+        }
+    }
+    update_recent_projects_undoable(config_dir, history, |recent_projects| {
+        recent_projects.clear_projects(all)
+    });
+    println!("{} Run `devenv undo` to restore them.", message);
+}
+
+fn undo(config_dir: &Path, history: &HistoryConfig) {
+    match RecentProjects::undo(config_dir, history) {
+        Ok(true) => println!("Restored the recent projects as they were before the last change."),
+        Ok(false) => println!("Nothing to undo."),
+        Err(e) => eprintln!("Failed to undo: {}", e),
     }
 }
 
-fn remove_project(index: usize, recent_projects: &mut RecentProjects, config_dir: &Path) {
-    if let Some(project) = recent_projects.remove_project(index) {
-        println!("Removed {} from recent projects", project.display());
-        save_recent_projects(config_dir, recent_projects);
+fn alias_project(
+    selector: &str,
+    alias: Option<String>,
+    config_dir: &Path,
+    history: &HistoryConfig,
+) {
+    update_recent_projects(config_dir, history, |recent_projects| {
+        let result = recent_projects
+            .find_project(selector)
+            .and_then(|index| recent_projects.set_alias(index, alias));
+        match result {
+            Ok(project) => println!("{} is now named {}", project.path.display(), project.name()),
+            Err(e) => eprintln!("{}", e),
+        }
+    });
+}
+
+fn prune_projects(
+    dry_run: bool,
+    recent_projects: &RecentProjects,
+    config_dir: &Path,
+    history: &HistoryConfig,
+) {
+    let pruned = if dry_run {
+        recent_projects.missing_projects()
     } else {
-        eprintln!("Invalid recent project index.");
+        update_recent_projects_undoable(config_dir, history, RecentProjects::prune)
+    };
+    if pruned.is_empty() {
+        println!("No missing recent projects.");
+        return;
+    }
+    let action = if dry_run { "Would remove" } else { "Removed" };
+    for project in &pruned {
+        println!("{} {}", action, project.path.display());
     }
 }
 
-fn clear_recent_projects(recent_projects: &mut RecentProjects, config_dir: &Path) {
-    recent_projects.clear_projects();
-    save_recent_projects(config_dir, recent_projects);
-    println!("Cleared all recent projects.");
+fn tag_project(
+    selector: &str,
+    tags: &[String],
+    remove: bool,
+    config_dir: &Path,
+    history: &HistoryConfig,
+) {
+    update_recent_projects(config_dir, history, |recent_projects| {
+        let index = match recent_projects.find_project(selector) {
+            Ok(index) => index,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        let project = if remove {
+            recent_projects.remove_tags(index, tags)
+        } else {
+            recent_projects.add_tags(index, tags)
+        };
+        if let Some(project) = project {
+            println!(
+                "Tags of {}: {}",
+                project.path.display(),
+                project.tags.join(", ")
+            );
+        }
+    });
+}
+
+fn pin_project(selector: &str, pinned: bool, config_dir: &Path, history: &HistoryConfig) {
+    update_recent_projects(config_dir, history, |recent_projects| {
+        let index = match recent_projects.find_project(selector) {
+            Ok(index) => index,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        if let Some(project) = recent_projects.set_pinned(index, pinned) {
+            let action = if pinned { "Pinned" } else { "Unpinned" };
+            println!("{} {}", action, project.path.display());
+        }
+    });
 }
 
-fn open_interactive_project(
-    recent_projects: &mut RecentProjects,
-    config: &mut Config,
-    config_path: &Path,
+fn export_history(recent_projects: &RecentProjects) {
+    match recent_projects.export() {
+        Ok(data) => println!("{}", data),
+        Err(e) => eprintln!("Failed to export recent projects: {}", e),
+    }
+}
+
+fn import_history(
+    file: &Path,
+    mappings: &[PathMapping],
     config_dir: &Path,
+    history: &HistoryConfig,
 ) {
-    if let Some(project) = recent_projects.interactive_menu() {
-        open_project(project, config, config_path, recent_projects, config_dir);
+    let data = match fs::read_to_string(file) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", file.display(), e);
+            return;
+        }
+    };
+    let imported = update_recent_projects_undoable(config_dir, history, |recent_projects| {
+        recent_projects.import(&data, mappings)
+    });
+    match imported {
+        Ok((added, merged, dropped)) => {
+            println!(
+                "Imported {} recent projects, {} of them merged into existing entries.",
+                added + merged,
+                merged
+            );
+            if dropped > 0 {
+                eprintln!(
+                    "{} imported projects were dropped to stay within the history limits, \
+                     raise `history.limit` in the configuration to keep them.",
+                    dropped
+                );
+            }
+        }
+        Err(e) => eprintln!("Failed to import {}: {}", file.display(), e),
     }
 }
 
-fn open_current_directory(
+fn open_interactive_project(
+    sort: SortOrder,
+    filter: &ProjectFilter,
+    recent_projects: &RecentProjects,
+    wait: bool,
     config: &mut Config,
     config_path: &Path,
-    recent_projects: &mut RecentProjects,
     config_dir: &Path,
 ) {
+    if let Some(project) = recent_projects.interactive_menu(sort, filter) {
+        open_project(project, &[], wait, config, config_path, config_dir);
+    }
+}
+
+fn open_current_directory(wait: bool, config: &mut Config, config_path: &Path, config_dir: &Path) {
     let project_dir = env::current_dir().expect("Failed to get current directory");
-    open_project(
-        project_dir,
-        config,
-        config_path,
-        recent_projects,
-        config_dir,
-    );
+    open_project(project_dir, &[], wait, config, config_path, config_dir);
+}
+
+fn run_setup(config: &mut Config, config_path: &Path) {
+    setup::run(config);
+    save_config(config_path, config);
+    eprintln!("Saved configuration to {}", config_path.display());
 }
 
 fn save_config(config_path: &Path, config: &mut Config) {
@@ -197,8 +543,21 @@ fn save_config(config_path: &Path, config: &mut Config) {
         .expect("Failed to save configuration.");
 }
 
-fn save_recent_projects(config_dir: &Path, recent_projects: &mut RecentProjects) {
-    recent_projects
-        .save(config_dir)
-        .expect("Failed to save recent projects.");
+/// Applies `change` to the recent projects as they are on disk, see `RecentProjects::update`.
+fn update_recent_projects<T>(
+    config_dir: &Path,
+    history: &HistoryConfig,
+    change: impl FnOnce(&mut RecentProjects) -> T,
+) -> T {
+    RecentProjects::update(config_dir, history, change).expect("Failed to save recent projects.")
+}
+
+/// Like `update_recent_projects`, but the change can be reverted with `devenv undo`.
+fn update_recent_projects_undoable<T>(
+    config_dir: &Path,
+    history: &HistoryConfig,
+    change: impl FnOnce(&mut RecentProjects) -> T,
+) -> T {
+    RecentProjects::update_undoable(config_dir, history, change)
+        .expect("Failed to save recent projects.")
 }
@@ -0,0 +1,54 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROJECT_SETTINGS_FILE: &str = ".devenv.toml";
+
+/// Settings stored inside a project directory that refine the global configuration.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    #[serde(default)]
+    pub launch: LaunchOverrides,
+    /// Environment variables for the processes launched for this project
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// A `.env` style file, relative to the project, loaded before `env`
+    #[serde(default)]
+    pub env_file: Option<PathBuf>,
+    /// Shell environment to evaluate in the project directory before launching tools
+    #[serde(default)]
+    pub activate: Option<Activation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    /// The environment exported by `direnv` for the project's `.envrc`
+    Direnv,
+    /// The environment of `nix-shell` for the project's `shell.nix` or `default.nix`
+    Nix,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LaunchOverrides {
+    /// Names of launch steps to run even if they are disabled in the configuration
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    /// Names of launch steps to skip for this project
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+impl ProjectSettings {
+    pub fn load(project_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = project_dir.join(PROJECT_SETTINGS_FILE);
+        if path.exists() {
+            let data = fs::read_to_string(path)?;
+            Ok(toml::from_str(&data)?)
+        } else {
+            Ok(ProjectSettings::default())
+        }
+    }
+}
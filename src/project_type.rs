@@ -1,11 +1,15 @@
+use crate::config::CustomProjectConfig;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ProjectType {
     Unity,
     Rust,
+    /// A project type defined in the `project_types` section of the configuration
+    Custom(String),
 }
 
 impl FromStr for ProjectType {
@@ -25,12 +29,18 @@ impl fmt::Display for ProjectType {
         match self {
             ProjectType::Unity => write!(f, "unity"),
             ProjectType::Rust => write!(f, "rust"),
+            ProjectType::Custom(name) => write!(f, "{}", name),
         }
     }
 }
 
 impl ProjectType {
-    pub(crate) fn from_path(project_path: &Path) -> Option<ProjectType> {
+    /// Detects the type of the project at `project_path`. Built-in types are checked before
+    /// the custom ones, which match when all of their marker paths exist.
+    pub(crate) fn from_path(
+        project_path: &Path,
+        custom_types: &BTreeMap<String, CustomProjectConfig>,
+    ) -> Option<ProjectType> {
         if project_path.join("Assets").exists()
             && project_path.join("Packages").exists()
             && project_path.join("ProjectSettings").exists()
@@ -42,7 +52,68 @@ impl ProjectType {
         {
             Some(ProjectType::Rust)
         } else {
-            None
+            custom_types
+                .iter()
+                .find(|(_, custom)| {
+                    !custom.markers.is_empty()
+                        && custom
+                            .markers
+                            .iter()
+                            .all(|marker| project_path.join(marker).exists())
+                })
+                .map(|(name, _)| ProjectType::Custom(name.clone()))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn custom_types() -> BTreeMap<String, CustomProjectConfig> {
+        let mut types = BTreeMap::new();
+        types.insert(
+            "node".to_string(),
+            CustomProjectConfig {
+                markers: vec![PathBuf::from("package.json"), PathBuf::from("src")],
+                ide_path: PathBuf::new(),
+                launch: Vec::new(),
+                env: BTreeMap::new(),
+            },
+        );
+        types
+    }
+
+    #[test]
+    fn test_from_path_custom_type() {
+        let temp_dir = tempdir().unwrap();
+        File::create(temp_dir.path().join("package.json")).unwrap();
+        assert_eq!(
+            ProjectType::from_path(temp_dir.path(), &custom_types()),
+            None
+        );
+
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        assert_eq!(
+            ProjectType::from_path(temp_dir.path(), &custom_types()),
+            Some(ProjectType::Custom("node".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_from_path_prefers_built_in_types() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        File::create(temp_dir.path().join("package.json")).unwrap();
+        File::create(temp_dir.path().join("Cargo.toml")).unwrap();
+        File::create(temp_dir.path().join("Cargo.lock")).unwrap();
+
+        assert_eq!(
+            ProjectType::from_path(temp_dir.path(), &custom_types()),
+            Some(ProjectType::Rust)
+        );
+    }
+}
@@ -1,15 +1,384 @@
+use crate::config::HistoryConfig;
+use crate::utils;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::Select;
+use serde::Deserializer;
 use serde_derive::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const RECENT_PROJECTS_FILE: &str = "recent_projects.toml";
+/// Keeps the lists as they were before destructive changes, for `devenv undo`
+const UNDO_FILE: &str = "recent_projects.undo.toml";
+/// Number of changes that can be undone
+const UNDO_GENERATIONS: usize = 5;
+/// Minimum Jaro-Winkler similarity for a name to match a misspelled selector
+const FUZZY_MATCH_THRESHOLD: f64 = 0.8;
+/// Once the scores of all projects add up to more than this, they are scaled down so that
+/// projects that are no longer opened lose their rank over time
+const MAX_TOTAL_SCORE: f64 = 100.0;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentProject {
+    pub path: PathBuf,
+    /// Short identifier that, unlike the index, doesn't change as the list changes
+    #[serde(default)]
+    pub id: String,
+    /// Configuration profile the project was last opened with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Name to open the project by instead of its folder name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Labels to filter the listings by
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Type the project was detected as when it was last opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_type: Option<String>,
+    /// Unity editor version of the project when it was last opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unity_version: Option<String>,
+    /// Git branch checked out when the project was last opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    /// Unix time in seconds the project was last opened at, 0 if unknown
+    #[serde(default)]
+    pub last_opened: u64,
+    /// Number of times the project was opened
+    #[serde(default)]
+    pub open_count: u32,
+    /// Rank that grows by one with every open and decays as other projects are opened
+    #[serde(default)]
+    pub score: f64,
+    /// Pinned projects are listed first and never dropped by the history limits or `clear`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+impl RecentProject {
+    pub fn new(path: PathBuf, profile: Option<String>) -> Self {
+        RecentProject {
+            id: project_id(&path),
+            path,
+            profile,
+            alias: None,
+            tags: Vec::new(),
+            project_type: None,
+            unity_version: None,
+            git_branch: None,
+            last_opened: 0,
+            open_count: 0,
+            score: 0.0,
+            pinned: false,
+        }
+    }
+
+    /// The alias of the project, or its folder name if it has none.
+    pub fn name(&self) -> &str {
+        match &self.alias {
+            Some(alias) => alias,
+            None => self
+                .path
+                .file_name()
+                .and_then(|os_str| os_str.to_str())
+                .unwrap_or("Unknown file"),
+        }
+    }
+
+    /// Combines the history of an entry stored under the same path into this one, taking
+    /// the details of whichever was opened last.
+    fn merge(&mut self, other: RecentProject) {
+        self.open_count += other.open_count;
+        self.score += other.score;
+        self.pinned |= other.pinned;
+        self.tags.extend(other.tags);
+        self.tags.sort();
+        self.tags.dedup();
+        if self.alias.is_none() {
+            self.alias = other.alias;
+        }
+        if other.last_opened > self.last_opened {
+            self.last_opened = other.last_opened;
+            self.profile = other.profile;
+            self.project_type = other.project_type;
+            self.unity_version = other.unity_version;
+            self.git_branch = other.git_branch;
+        }
+    }
+
+    /// Whether the project directory was moved or deleted.
+    pub fn is_missing(&self) -> bool {
+        !self.path.is_dir()
+    }
+
+    /// Score weighted by how recently the project was opened, like zoxide does.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_opened);
+        let weight = match age {
+            0..=3599 => 4.0,
+            3600..=86_399 => 2.0,
+            86_400..=604_799 => 0.5,
+            _ => 0.25,
+        };
+        self.score * weight
+    }
+}
+
+/// What was detected about a project when it was opened.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectDetails {
+    pub project_type: Option<String>,
+    pub unity_version: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+/// Restricts listings to the projects with all of `tags` and, if set, of `project_type`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectFilter {
+    pub tags: Vec<String>,
+    pub project_type: Option<String>,
+}
+
+impl ProjectFilter {
+    pub fn matches(&self, project: &RecentProject) -> bool {
+        let has_tags = self.tags.iter().all(|tag| project.tags.contains(tag));
+        let has_type = match &self.project_type {
+            Some(wanted) => project
+                .project_type
+                .as_ref()
+                .is_some_and(|project_type| project_type.eq_ignore_ascii_case(wanted)),
+            None => true,
+        };
+        has_tags && has_type
+    }
+}
+
+/// Order of the recent projects in listings.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SortOrder {
+    /// Frequently and recently opened projects first
+    #[default]
+    Frecency,
+    /// Most recently opened projects first
+    Recent,
+    /// Most often opened projects first
+    Frequent,
+    /// Alphabetically by project name
+    Name,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "frecency" => Ok(SortOrder::Frecency),
+            "recent" => Ok(SortOrder::Recent),
+            "frequent" => Ok(SortOrder::Frequent),
+            "name" => Ok(SortOrder::Name),
+            _ => Err(format!(
+                "'{}' is not a valid sort order, expected frecency, recent, frequent or name",
+                s
+            )),
+        }
+    }
+}
+
+/// Rewrites paths under `from` to the same place under `to`, written as `from=to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathMapping {
+    pub from: String,
+    pub to: PathBuf,
+}
+
+impl FromStr for PathMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(PathMapping {
+                from: from.to_string(),
+                to: PathBuf::from(to),
+            }),
+            _ => Err(format!("'{}' is not a valid mapping, expected from=to", s)),
+        }
+    }
+}
+
+impl PathMapping {
+    /// The path `path` maps to, if it is `from` or lies below it. The rest of the path is
+    /// split on both separators, so paths exported on Windows can be mapped on Unix and the
+    /// other way round.
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        let path = path.to_string_lossy();
+        let from = self.from.trim_end_matches(['/', '\\']);
+        let rest = path.strip_prefix(from)?;
+        if !rest.is_empty() && !rest.starts_with(['/', '\\']) {
+            return None;
+        }
+        Some(
+            rest.split(['/', '\\'])
+                .filter(|component| !component.is_empty())
+                .fold(self.to.clone(), |path, component| path.join(component)),
+        )
+    }
+}
+
+/// Derives the identifier of a project from its canonical path with the 64-bit FNV-1a hash.
+pub fn project_id(path: &Path) -> String {
+    let hash = utils::path_key(&utils::canonical_path(path))
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)[..8].to_string()
+}
+
+/// Current Unix time in seconds.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats how long ago `then` was, relative to `now`, e.g. "2h ago".
+pub fn format_relative_time(then: u64, now: u64) -> String {
+    if then == 0 {
+        return "never".to_string();
+    }
+    let seconds = now.saturating_sub(then);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86_399 => format!("{}h ago", seconds / 3600),
+        86_400..=2_591_999 => format!("{}d ago", seconds / 86_400),
+        _ => format!("{}mo ago", seconds / 2_592_000),
+    }
+}
+
+/// Recently opened projects, pinned ones first and then ranked by frecency. Indices always
+/// refer to this order.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct RecentProjects {
-    projects: Vec<PathBuf>,
+    #[serde(deserialize_with = "deserialize_projects")]
+    projects: Vec<RecentProject>,
+    /// Limits applied when a project is added and when the list is saved
+    #[serde(skip)]
+    limits: HistoryConfig,
+}
+
+/// Accepts both the current entry tables and the plain path list written by older versions,
+/// which kept the most recently added project last. Paths are canonicalised, and entries
+/// that turn out to be the same project are merged.
+fn deserialize_projects<'de, D>(deserializer: D) -> Result<Vec<RecentProject>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredProject {
+        Path(PathBuf),
+        Entry(RecentProject),
+    }
+
+    let stored: Vec<StoredProject> = serde::Deserialize::deserialize(deserializer)?;
+    let legacy = stored
+        .iter()
+        .all(|project| matches!(project, StoredProject::Path(_)));
+    let mut projects: Vec<RecentProject> = stored
+        .into_iter()
+        .map(|project| match project {
+            StoredProject::Path(path) => RecentProject::new(path, None),
+            StoredProject::Entry(mut entry) => {
+                if entry.id.is_empty() {
+                    entry.id = project_id(&entry.path);
+                }
+                entry
+            }
+        })
+        .collect();
+    if legacy {
+        projects.reverse();
+    }
+
+    let mut unique: Vec<RecentProject> = Vec::new();
+    for mut project in projects {
+        project.path = utils::canonical_path(&project.path);
+        insert_or_merge(&mut unique, project);
+    }
+    sort_by_frecency(&mut unique, unix_timestamp());
+    Ok(unique)
+}
+
+/// Adds `project` to `projects`, merging it into the entry with the same path if there is
+/// one. Returns whether it was merged.
+fn insert_or_merge(projects: &mut Vec<RecentProject>, project: RecentProject) -> bool {
+    let key = utils::path_key(&project.path);
+    match projects
+        .iter_mut()
+        .find(|p| utils::path_key(&p.path) == key)
+    {
+        Some(existing) => {
+            existing.merge(project);
+            true
+        }
+        None => {
+            projects.push(project);
+            false
+        }
+    }
+}
+
+/// Earlier states of the list, most recent first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UndoHistory {
+    #[serde(default)]
+    snapshots: Vec<RecentProjects>,
+}
+
+impl UndoHistory {
+    fn load(config_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = config_dir.join(UNDO_FILE);
+        if path.exists() {
+            let data = fs::read_to_string(path)?;
+            Ok(toml::from_str(&data)?)
+        } else {
+            Ok(UndoHistory::default())
+        }
+    }
+
+    fn save(&self, config_dir: &Path) -> Result<(), Box<dyn Error>> {
+        let data = toml::to_string_pretty(self)?;
+        utils::write_atomic(&config_dir.join(UNDO_FILE), data.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Layout of `devenv history export`, read back as is so that paths can be remapped before
+/// they are canonicalised.
+#[derive(Deserialize)]
+struct ExportedHistory {
+    projects: Vec<RecentProject>,
+}
+
+/// Sorts pinned projects first and then by frecency, breaking ties by the last opened time.
+fn sort_by_frecency(projects: &mut [RecentProject], now: u64) {
+    projects.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(
+                b.frecency(now)
+                    .partial_cmp(&a.frecency(now))
+                    .unwrap_or(Ordering::Equal),
+            )
+            .then(b.last_opened.cmp(&a.last_opened))
+    });
 }
 
 impl RecentProjects {
@@ -20,30 +389,195 @@ impl RecentProjects {
             let projects: RecentProjects = toml::from_str(&data)?;
             Ok(projects)
         } else {
-            Ok(RecentProjects {
-                projects: Vec::new(),
-            })
+            Ok(RecentProjects::default())
         }
     }
 
-    pub fn save(&self, config_dir: &Path) -> Result<(), Box<dyn Error>> {
+    pub fn set_limits(&mut self, limits: HistoryConfig) {
+        self.limits = limits;
+    }
+
+    /// Saves the list, first dropping the projects over the limits.
+    pub fn save(&mut self, config_dir: &Path) -> Result<(), Box<dyn Error>> {
+        self.trim();
         let path = config_dir.join(RECENT_PROJECTS_FILE);
         let data = toml::to_string_pretty(&self)?;
-        fs::write(path, data)?;
+        utils::write_atomic(&path, data.as_bytes())?;
         Ok(())
     }
 
-    pub fn add_project(&mut self, project: PathBuf) {
-        if self.projects.contains(&project) {
+    /// Loads the list, applies `change` and saves it while holding a lock, so that devenv
+    /// running in several terminals at once doesn't lose any changes.
+    pub fn update<T>(
+        config_dir: &Path,
+        limits: &HistoryConfig,
+        change: impl FnOnce(&mut RecentProjects) -> T,
+    ) -> Result<T, Box<dyn Error>> {
+        let _lock = utils::lock_file(&config_dir.join(RECENT_PROJECTS_FILE))?;
+        let mut recent_projects = RecentProjects::load(config_dir)?;
+        recent_projects.set_limits(limits.clone());
+        let result = change(&mut recent_projects);
+        recent_projects.save(config_dir)?;
+        Ok(result)
+    }
+
+    /// Like `update`, but if `change` alters the list its previous state is kept so that
+    /// `undo` can restore it.
+    pub fn update_undoable<T>(
+        config_dir: &Path,
+        limits: &HistoryConfig,
+        change: impl FnOnce(&mut RecentProjects) -> T,
+    ) -> Result<T, Box<dyn Error>> {
+        RecentProjects::update(config_dir, limits, |recent_projects| {
+            let before = recent_projects.projects.clone();
+            let result = change(recent_projects);
+            if recent_projects.projects != before {
+                let mut history = UndoHistory::load(config_dir)?;
+                let snapshot = RecentProjects {
+                    projects: before,
+                    limits: HistoryConfig::default(),
+                };
+                history.snapshots.insert(0, snapshot);
+                history.snapshots.truncate(UNDO_GENERATIONS);
+                history.save(config_dir)?;
+            }
+            Ok(result)
+        })?
+    }
+
+    /// Restores the list as it was before the last change made with `update_undoable`.
+    /// Returns `false` when there is nothing left to undo.
+    pub fn undo(config_dir: &Path, limits: &HistoryConfig) -> Result<bool, Box<dyn Error>> {
+        RecentProjects::update(config_dir, limits, |recent_projects| {
+            let mut history = UndoHistory::load(config_dir)?;
+            if history.snapshots.is_empty() {
+                return Ok(false);
+            }
+            let snapshot = history.snapshots.remove(0);
+            history.save(config_dir)?;
+            recent_projects.projects = snapshot.projects;
+            Ok(true)
+        })?
+    }
+
+    /// The list as JSON, with aliases, tags and pins, for `import` on another machine.
+    pub fn export(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Merges the projects of an `export` into the list, rewriting their paths with the first
+    /// matching mapping, and drops the projects over the limits. Returns how many projects
+    /// were added, how many were merged into existing entries and how many of both were
+    /// dropped again by the limits.
+    pub fn import(
+        &mut self,
+        data: &str,
+        mappings: &[PathMapping],
+    ) -> Result<(usize, usize, usize), serde_json::Error> {
+        let exported: ExportedHistory = serde_json::from_str(data)?;
+        let (mut added, mut merged) = (0, 0);
+        let mut imported = Vec::new();
+        for mut project in exported.projects {
+            if let Some(path) = mappings.iter().find_map(|m| m.apply(&project.path)) {
+                project.path = path;
+            }
+            project.path = utils::canonical_path(&project.path);
+            project.id = project_id(&project.path);
+            imported.push(project.path.clone());
+            if insert_or_merge(&mut self.projects, project) {
+                merged += 1;
+            } else {
+                added += 1;
+            }
+        }
+        sort_by_frecency(&mut self.projects, unix_timestamp());
+        self.trim();
+        let dropped = imported
+            .iter()
+            .filter(|path| !self.projects.iter().any(|p| &p.path == *path))
+            .count();
+        Ok((added, merged, dropped))
+    }
+
+    /// Records that the project was opened now and re-ranks the projects. The least recently
+    /// opened projects are dropped once the list is over its limits.
+    pub fn add_project(
+        &mut self,
+        project: PathBuf,
+        profile: Option<String>,
+        details: ProjectDetails,
+    ) {
+        self.add_project_at(project, profile, details, unix_timestamp());
+    }
+
+    fn add_project_at(
+        &mut self,
+        project: PathBuf,
+        profile: Option<String>,
+        details: ProjectDetails,
+        opened: u64,
+    ) {
+        let project = utils::canonical_path(&project);
+        let mut entry = match self.position(&project) {
+            Some(index) => self.projects.remove(index),
+            None => RecentProject::new(project, None),
+        };
+        entry.profile = profile;
+        entry.project_type = details.project_type;
+        entry.unity_version = details.unity_version;
+        entry.git_branch = details.git_branch;
+        entry.last_opened = opened;
+        entry.open_count += 1;
+        entry.score += 1.0;
+        self.projects.insert(0, entry);
+        self.trim();
+
+        let total: f64 = self.projects.iter().map(|p| p.score).sum();
+        if total > MAX_TOTAL_SCORE {
+            let factor = 0.9 * MAX_TOTAL_SCORE / total;
+            for project in &mut self.projects {
+                project.score *= factor;
+            }
+        }
+        sort_by_frecency(&mut self.projects, opened);
+    }
+
+    /// Drops the least recently opened projects until every project type is within its limit
+    /// and the whole list is within `limit`. A limit of 0 means no limit. Pinned projects
+    /// are neither counted nor dropped.
+    fn trim(&mut self) {
+        let type_limits = self.limits.type_limits.clone();
+        for (project_type, limit) in type_limits {
+            self.evict_over(limit, |p| {
+                !p.pinned && p.project_type.as_deref() == Some(project_type.as_str())
+            });
+        }
+        self.evict_over(self.limits.limit, |p| !p.pinned);
+    }
+
+    fn evict_over(&mut self, limit: usize, matches: impl Fn(&RecentProject) -> bool) {
+        if limit == 0 {
             return;
         }
-        self.projects.push(project);
-        if self.projects.len() > 10 {
-            self.projects.remove(0);
+        while self.projects.iter().filter(|p| matches(p)).count() > limit {
+            let oldest = self
+                .projects
+                .iter()
+                .enumerate()
+                // Legacy and imported projects all share a `last_opened` of 0, the list keeps
+                // them newest first so the last of them is the least recently opened
+                .rev()
+                .filter(|(_, p)| matches(p))
+                .min_by_key(|(_, p)| p.last_opened)
+                .map(|(index, _)| index);
+            match oldest {
+                Some(index) => self.projects.remove(index),
+                None => break,
+            };
         }
     }
 
-    pub fn remove_project(&mut self, index: usize) -> Option<PathBuf> {
+    pub fn remove_project(&mut self, index: usize) -> Option<RecentProject> {
         if index < self.projects.len() {
             Some(self.projects.remove(index))
         } else {
@@ -51,61 +585,335 @@ impl RecentProjects {
         }
     }
 
-    pub fn clear_projects(&mut self) {
-        self.projects.clear()
+    /// Removes every project, or every project but the pinned ones unless `all` is set.
+    pub fn clear_projects(&mut self, all: bool) {
+        if all {
+            self.projects.clear()
+        } else {
+            self.projects.retain(|p| p.pinned)
+        }
     }
 
-    pub fn get_project(&self, index: usize) -> Option<&PathBuf> {
+    pub fn get_project(&self, index: usize) -> Option<&RecentProject> {
         self.projects.get(index)
     }
 
-    pub fn list_projects(&self) {
-        for (index, project) in self.projects.iter().enumerate() {
-            println!("{}: {}", index, project.display());
+    /// Finds the index of a project given its ID, its index or its exact name.
+    pub fn find_project(&self, selector: &str) -> Result<usize, String> {
+        if let Some(index) = self.projects.iter().position(|p| p.id == selector) {
+            return Ok(index);
+        }
+        if let Ok(index) = selector.parse::<usize>() {
+            return if index < self.projects.len() {
+                Ok(index)
+            } else {
+                Err("Invalid recent project index.".to_string())
+            };
+        }
+        let matches: Vec<usize> = self
+            .projects
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.name() == selector)
+            .map(|(index, _)| index)
+            .collect();
+        match matches.as_slice() {
+            [index] => Ok(*index),
+            [] => Err(format!("No recent project is named '{}'.", selector)),
+            _ => Err(format!(
+                "Several recent projects are named '{}', use an index instead.",
+                selector
+            )),
+        }
+    }
+
+    /// Indices of the projects `selector` refers to. An ID, index or exact name wins, then
+    /// names starting with the selector, then names similar to it, best match first.
+    pub fn matching_projects(&self, selector: &str) -> Vec<usize> {
+        if let Ok(index) = self.find_project(selector) {
+            return vec![index];
+        }
+        let exact = self.indices_where(|p| p.name() == selector);
+        if !exact.is_empty() {
+            return exact;
+        }
+
+        let selector = selector.to_lowercase();
+        let prefixed = self.indices_where(|p| p.name().to_lowercase().starts_with(&selector));
+        if !prefixed.is_empty() {
+            return prefixed;
         }
+
+        let mut similar: Vec<(usize, f64)> = self
+            .projects
+            .iter()
+            .enumerate()
+            .map(|(index, p)| {
+                let similarity = strsim::jaro_winkler(&p.name().to_lowercase(), &selector);
+                (index, similarity)
+            })
+            .filter(|(_, similarity)| *similarity >= FUZZY_MATCH_THRESHOLD)
+            .collect();
+        similar.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        similar.into_iter().map(|(index, _)| index).collect()
     }
 
-    fn format_project_display(name: &str, path: &str, max_name_length: usize) -> String {
+    fn indices_where(&self, predicate: impl Fn(&RecentProject) -> bool) -> Vec<usize> {
+        self.projects
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| predicate(p))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Gives the project at `index` an alias, or removes it when `alias` is `None`.
+    pub fn set_alias(
+        &mut self,
+        index: usize,
+        alias: Option<String>,
+    ) -> Result<&RecentProject, String> {
+        if let Some(alias) = &alias {
+            if alias.parse::<usize>().is_ok() {
+                return Err("An alias can't be a number, it would be taken for an index.".into());
+            }
+            let taken = self
+                .projects
+                .iter()
+                .enumerate()
+                .any(|(other, p)| other != index && p.name() == alias);
+            if taken {
+                return Err(format!(
+                    "Another recent project is already named '{}'.",
+                    alias
+                ));
+            }
+        }
+        let project = self
+            .projects
+            .get_mut(index)
+            .ok_or("Invalid recent project index.")?;
+        project.alias = alias;
+        Ok(project)
+    }
+
+    /// The projects whose directory no longer exists.
+    pub fn missing_projects(&self) -> Vec<RecentProject> {
+        self.projects
+            .iter()
+            .filter(|p| p.is_missing())
+            .cloned()
+            .collect()
+    }
+
+    /// Removes the projects whose directory no longer exists and returns them.
+    pub fn prune(&mut self) -> Vec<RecentProject> {
+        let (missing, present) = self.projects.drain(..).partition(RecentProject::is_missing);
+        self.projects = present;
+        missing
+    }
+
+    /// Index of the project stored under `path`.
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        let key = utils::path_key(&utils::canonical_path(path));
+        self.projects
+            .iter()
+            .position(|p| utils::path_key(&p.path) == key)
+    }
+
+    /// Adds `tags` to the project at `index`, keeping its tags sorted and unique.
+    pub fn add_tags(&mut self, index: usize, tags: &[String]) -> Option<&RecentProject> {
+        let project = self.projects.get_mut(index)?;
+        project.tags.extend(tags.iter().cloned());
+        project.tags.sort();
+        project.tags.dedup();
+        Some(project)
+    }
+
+    pub fn remove_tags(&mut self, index: usize, tags: &[String]) -> Option<&RecentProject> {
+        let project = self.projects.get_mut(index)?;
+        project.tags.retain(|tag| !tags.contains(tag));
+        Some(project)
+    }
+
+    /// Pins or unpins the project at `index` and returns it.
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) -> Option<&RecentProject> {
+        let path = {
+            let project = self.projects.get_mut(index)?;
+            project.pinned = pinned;
+            project.path.clone()
+        };
+        sort_by_frecency(&mut self.projects, unix_timestamp());
+        self.projects.iter().find(|p| p.path == path)
+    }
+
+    /// The projects matching `filter` with their indices, in the given order.
+    pub fn sorted(&self, sort: SortOrder, filter: &ProjectFilter) -> Vec<(usize, &RecentProject)> {
+        let mut projects: Vec<(usize, &RecentProject)> = self
+            .projects
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| filter.matches(p))
+            .collect();
+        match sort {
+            SortOrder::Frecency => {}
+            SortOrder::Recent => projects.sort_by_key(|(_, p)| Reverse(p.last_opened)),
+            SortOrder::Frequent => projects.sort_by_key(|(_, p)| Reverse(p.open_count)),
+            SortOrder::Name => projects.sort_by_key(|(_, p)| p.name().to_lowercase()),
+        }
+        // Pinned projects stay on top whatever the order
+        projects.sort_by_key(|(_, p)| !p.pinned);
+        projects
+    }
+
+    pub fn list_projects(&self, sort: SortOrder, filter: &ProjectFilter, long: bool) {
+        let now = unix_timestamp();
+        for (index, project) in self.sorted(sort, filter) {
+            if long {
+                Self::print_details(index, project, now);
+                continue;
+            }
+            let mut opened = format!(
+                "{}, opened {} times",
+                format_relative_time(project.last_opened, now),
+                project.open_count
+            );
+            for tag in &project.tags {
+                opened.push_str(&format!(" #{}", tag));
+            }
+            if project.is_missing() {
+                opened.push_str(" (missing)");
+            }
+            let marker = if project.pinned { "* " } else { "" };
+            match &project.profile {
+                Some(profile) => println!(
+                    "{}: [{}] {}{} ({}) - {}",
+                    index,
+                    project.id,
+                    marker,
+                    project.path.display(),
+                    profile,
+                    opened
+                ),
+                None => println!(
+                    "{}: [{}] {}{} - {}",
+                    index,
+                    project.id,
+                    marker,
+                    project.path.display(),
+                    opened
+                ),
+            }
+        }
+    }
+
+    /// Prints everything recorded about a project, one field per line.
+    fn print_details(index: usize, project: &RecentProject, now: u64) {
+        let marker = if project.pinned { " (pinned)" } else { "" };
+        println!("{}: [{}] {}{}", index, project.id, project.name(), marker);
+        println!("    path:    {}", project.path.display());
+        if project.is_missing() {
+            println!("    missing: the directory no longer exists");
+        }
+        if let Some(project_type) = &project.project_type {
+            match &project.unity_version {
+                Some(version) => println!("    type:    {} {}", project_type, version),
+                None => println!("    type:    {}", project_type),
+            }
+        }
+        if let Some(branch) = &project.git_branch {
+            println!("    branch:  {}", branch);
+        }
+        if let Some(profile) = &project.profile {
+            println!("    profile: {}", profile);
+        }
+        println!(
+            "    opened:  {}, {} times",
+            format_relative_time(project.last_opened, now),
+            project.open_count
+        );
+        if !project.tags.is_empty() {
+            println!("    tags:    {}", project.tags.join(", "));
+        }
+    }
+
+    fn format_project_display(
+        pinned: bool,
+        name: &str,
+        path: &str,
+        opened: &str,
+        max_name_length: usize,
+    ) -> String {
         // Format the project name to be left-aligned and padded to the maximum length
         let formatted_name = format!("{:<width$}", name, width = max_name_length);
+        let marker = if pinned { "*" } else { " " };
+
+        // Combine the formatted name, path and time with a separator (e.g., tabs or spaces)
+        format!("{} {}    {}    {}", marker, formatted_name, path, opened)
+    }
 
-        // Combine the formatted name and path with a separator (e.g., tabs or spaces)
-        format!("{}    {}", formatted_name, path)
+    /// Resolves `selector` like `matching_projects`, asking which project was meant when
+    /// several match.
+    pub fn select_project(&self, selector: &str) -> Result<usize, String> {
+        let matches = self.matching_projects(selector);
+        match matches.as_slice() {
+            [] => Err(format!("No recent project matches '{}'.", selector)),
+            [index] => Ok(*index),
+            _ => {
+                let items: Vec<String> = matches
+                    .iter()
+                    .map(|&index| {
+                        let p = &self.projects[index];
+                        format!("{}    {}", p.name(), p.path.display())
+                    })
+                    .collect();
+                let selection = Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("Several projects match '{}'", selector))
+                    .default(0)
+                    .items(&items)
+                    .interact()
+                    .map_err(|e| e.to_string())?;
+                Ok(matches[selection])
+            }
+        }
     }
 
-    pub fn interactive_menu(&self) -> Option<PathBuf> {
+    pub fn interactive_menu(&self, sort: SortOrder, filter: &ProjectFilter) -> Option<PathBuf> {
         if self.projects.is_empty() {
             println!("No recent projects available.");
             return None;
         }
 
+        let projects = self.sorted(sort, filter);
+        if projects.is_empty() {
+            println!("No recent projects match the filter.");
+            return None;
+        }
+
         // Calculate the maximum length of the project names
-        let max_name_length = self
-            .projects
+        let max_name_length = projects
             .iter()
-            .map(|p| {
-                p.file_name()
-                    .and_then(|os_str| os_str.to_str())
-                    .unwrap_or("Unknown file")
-                    .len()
-            })
+            .map(|(_, p)| p.name().len())
             .max()
             .unwrap_or(0);
 
-        let items: Vec<String> = self
-            .projects
+        let now = unix_timestamp();
+        let items: Vec<String> = projects
             .iter()
-            .map(|p| {
-                let file_name = p
-                    .file_name()
-                    .and_then(|os_str| os_str.to_str())
-                    .unwrap_or("Unknown file");
+            .map(|(_, p)| {
+                let file_name = p.name();
                 let parent = p
+                    .path
                     .parent()
                     .and_then(|os_str| os_str.to_str())
                     .unwrap_or("Unknown parent");
 
-                Self::format_project_display(file_name, parent, max_name_length)
+                let mut opened = format_relative_time(p.last_opened, now);
+                if p.is_missing() {
+                    opened.push_str(" (missing)");
+                }
+
+                Self::format_project_display(p.pinned, file_name, parent, &opened, max_name_length)
             })
             .collect();
 
@@ -116,15 +924,28 @@ impl RecentProjects {
             .interact()
             .ok()?;
 
-        self.get_project(selection).cloned()
+        projects.get(selection).map(|(_, p)| p.path.clone())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
 
+    /// `path` as the list stores it, which on Windows is on the current drive.
+    fn project_path(path: &str) -> PathBuf {
+        utils::canonical_path(Path::new(path))
+    }
+
+    fn of_type(project_type: &str) -> ProjectDetails {
+        ProjectDetails {
+            project_type: Some(project_type.to_string()),
+            ..ProjectDetails::default()
+        }
+    }
+
     #[test]
     fn test_load_non_existent_file() {
         let temp_dir = tempdir().unwrap();
@@ -137,95 +958,761 @@ mod tests {
     fn test_save_and_load() {
         let temp_dir = tempdir().unwrap();
         let config_dir = temp_dir.path();
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
-        recent_projects.add_project(PathBuf::from("/project1"));
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
         recent_projects.save(config_dir).unwrap();
 
         let loaded_projects = RecentProjects::load(config_dir).unwrap();
         assert_eq!(loaded_projects.projects.len(), 1);
-        assert_eq!(loaded_projects.projects[0], PathBuf::from("/project1"));
+        assert_eq!(loaded_projects.projects[0].path, project_path("/project1"));
+    }
+
+    #[test]
+    fn test_load_legacy_path_list() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        fs::write(
+            config_dir.join(RECENT_PROJECTS_FILE),
+            "projects = [\"/project1\", \"/project2\"]\n",
+        )
+        .unwrap();
+
+        let recent_projects = RecentProjects::load(config_dir).unwrap();
+        assert_eq!(recent_projects.projects.len(), 2);
+        assert_eq!(recent_projects.projects[0].path, project_path("/project2"));
+        assert_eq!(recent_projects.projects[0].profile, None);
+        assert_eq!(recent_projects.projects[0].last_opened, 0);
+    }
+
+    #[test]
+    fn test_add_project_records_profile() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
+        recent_projects.add_project(
+            project_path("/project1"),
+            Some("work".to_string()),
+            ProjectDetails::default(),
+        );
+        assert_eq!(recent_projects.projects.len(), 1);
+        assert_eq!(recent_projects.projects[0].profile.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_reopen_moves_project_to_top() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(
+            project_path("/project1"),
+            None,
+            ProjectDetails::default(),
+            100,
+        );
+        recent_projects.add_project_at(
+            project_path("/project2"),
+            None,
+            ProjectDetails::default(),
+            200,
+        );
+        recent_projects.add_project_at(
+            project_path("/project1"),
+            None,
+            ProjectDetails::default(),
+            300,
+        );
+        let paths: Vec<&Path> = recent_projects
+            .projects
+            .iter()
+            .map(|p| p.path.as_path())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![&project_path("/project1"), &project_path("/project2")]
+        );
+        assert_eq!(recent_projects.projects[0].last_opened, 300);
+    }
+
+    #[test]
+    fn test_load_sorts_by_last_opened() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        fs::write(
+            config_dir.join(RECENT_PROJECTS_FILE),
+            r#"
+[[projects]]
+path = "/old"
+last_opened = 100
+
+[[projects]]
+path = "/new"
+last_opened = 200
+"#,
+        )
+        .unwrap();
+
+        let recent_projects = RecentProjects::load(config_dir).unwrap();
+        assert_eq!(recent_projects.projects[0].path, project_path("/new"));
+    }
+
+    #[test]
+    fn test_frecency_ranking() {
+        let mut recent_projects = RecentProjects::default();
+        let day = 86_400;
+        for _ in 0..5 {
+            recent_projects.add_project_at(
+                project_path("/frequent"),
+                None,
+                ProjectDetails::default(),
+                0,
+            );
+        }
+        recent_projects.add_project_at(
+            project_path("/recent"),
+            None,
+            ProjectDetails::default(),
+            3 * day,
+        );
+        // Opened five times but days ago, it ranks below the project opened just now
+        assert_eq!(recent_projects.projects[0].path, project_path("/recent"));
+        assert_eq!(recent_projects.projects[1].open_count, 5);
+
+        recent_projects.add_project_at(
+            project_path("/frequent"),
+            None,
+            ProjectDetails::default(),
+            3 * day + 60,
+        );
+        assert_eq!(recent_projects.projects[0].path, project_path("/frequent"));
+
+        let by_name: Vec<usize> = recent_projects
+            .sorted(SortOrder::Name, &ProjectFilter::default())
+            .iter()
+            .map(|(index, _)| *index)
+            .collect();
+        assert_eq!(by_name, vec![0, 1]);
+        let by_recent: Vec<&str> = recent_projects
+            .sorted(SortOrder::Recent, &ProjectFilter::default())
+            .iter()
+            .map(|(_, p)| p.name())
+            .collect();
+        assert_eq!(by_recent, vec!["frequent", "recent"]);
+    }
+
+    #[test]
+    fn test_scores_decay() {
+        let mut recent_projects = RecentProjects::default();
+        for i in 0..150 {
+            recent_projects.add_project_at(
+                project_path(&format!("/project{}", i % 3)),
+                None,
+                ProjectDetails::default(),
+                i,
+            );
+        }
+        let total: f64 = recent_projects.projects.iter().map(|p| p.score).sum();
+        assert!(total <= MAX_TOTAL_SCORE);
+        assert_eq!(recent_projects.projects[0].open_count, 50);
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        assert_eq!(format_relative_time(0, 1000), "never");
+        assert_eq!(format_relative_time(1000, 1030), "just now");
+        assert_eq!(format_relative_time(1000, 1000 + 5 * 60), "5m ago");
+        assert_eq!(format_relative_time(1000, 1000 + 2 * 3600), "2h ago");
+        assert_eq!(format_relative_time(1000, 1000 + 3 * 86_400), "3d ago");
     }
 
     #[test]
     fn test_add_project() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
-        recent_projects.add_project(PathBuf::from("/project1"));
-        recent_projects.add_project(PathBuf::from("/project2"));
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
+        recent_projects.add_project(project_path("/project2"), None, ProjectDetails::default());
         assert_eq!(recent_projects.projects.len(), 2);
     }
 
     #[test]
     fn test_add_project_duplicate() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
-        recent_projects.add_project(PathBuf::from("/project1"));
-        recent_projects.add_project(PathBuf::from("/project1"));
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
         assert_eq!(recent_projects.projects.len(), 1);
     }
 
     #[test]
     fn test_add_project_limit() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
+        let mut recent_projects = RecentProjects::default();
         for i in 0..11 {
-            recent_projects.add_project(PathBuf::from(format!("/project{}", i)));
+            recent_projects.add_project_at(
+                project_path(&format!("/project{}", i)),
+                None,
+                ProjectDetails::default(),
+                i,
+            );
         }
+        // /project0 was evicted, opening it again evicts the next least recently used
+        recent_projects.add_project_at(
+            project_path("/project0"),
+            None,
+            ProjectDetails::default(),
+            11,
+        );
+        recent_projects.add_project_at(
+            project_path("/project11"),
+            None,
+            ProjectDetails::default(),
+            12,
+        );
         assert_eq!(recent_projects.projects.len(), 10);
-        assert_eq!(recent_projects.projects[0], PathBuf::from("/project1"));
+        assert_eq!(recent_projects.projects[0].path, project_path("/project11"));
+        assert!(recent_projects
+            .projects
+            .iter()
+            .all(|p| p.path != project_path("/project1") && p.path != project_path("/project2")));
+        assert!(recent_projects
+            .projects
+            .iter()
+            .any(|p| p.path == project_path("/project0")));
+    }
+
+    #[test]
+    fn test_add_project_limit_after_legacy_list() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        let paths: Vec<String> = (0..10).map(|i| format!("\"/p{}\"", i)).collect();
+        fs::write(
+            config_dir.join(RECENT_PROJECTS_FILE),
+            format!("projects = [{}]\n", paths.join(", ")),
+        )
+        .unwrap();
+
+        let mut recent_projects = RecentProjects::load(config_dir).unwrap();
+        recent_projects.add_project(project_path("/new"), None, ProjectDetails::default());
+        assert_eq!(recent_projects.projects.len(), 10);
+        assert!(recent_projects
+            .projects
+            .iter()
+            .all(|p| p.path != project_path("/p0")));
+        assert!(recent_projects
+            .projects
+            .iter()
+            .any(|p| p.path == project_path("/p9")));
+    }
+
+    #[test]
+    fn test_type_limits() {
+        let mut recent_projects = RecentProjects::default();
+        let mut type_limits = BTreeMap::new();
+        type_limits.insert("unity".to_string(), 2);
+        recent_projects.set_limits(HistoryConfig {
+            limit: 4,
+            type_limits,
+            ..HistoryConfig::default()
+        });
+        for i in 0..3 {
+            let path = project_path(&format!("/unity{}", i));
+            recent_projects.add_project_at(path, None, of_type("unity"), i);
+        }
+        for i in 3..6 {
+            let path = project_path(&format!("/rust{}", i));
+            recent_projects.add_project_at(path, None, of_type("rust"), i);
+        }
+        let mut names: Vec<&str> = recent_projects.projects.iter().map(|p| p.name()).collect();
+        names.sort();
+        assert_eq!(names, vec!["rust3", "rust4", "rust5", "unity2"]);
+    }
+
+    #[test]
+    fn test_unlimited_history_trimmed_on_save() {
+        let temp_dir = tempdir().unwrap();
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.set_limits(HistoryConfig {
+            limit: 0,
+            ..HistoryConfig::default()
+        });
+        for i in 0..30 {
+            let path = project_path(&format!("/project{}", i));
+            recent_projects.add_project_at(path, None, ProjectDetails::default(), i);
+        }
+        assert_eq!(recent_projects.projects.len(), 30);
+
+        recent_projects.set_limits(HistoryConfig::default());
+        recent_projects.save(temp_dir.path()).unwrap();
+        let loaded = RecentProjects::load(temp_dir.path()).unwrap();
+        assert_eq!(loaded.projects.len(), 10);
+        assert!(loaded.projects.iter().all(|p| p.last_opened >= 20));
     }
 
     #[test]
     fn test_remove_project() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
-        recent_projects.add_project(PathBuf::from("/project1"));
-        recent_projects.add_project(PathBuf::from("/project2"));
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
+        recent_projects.add_project(project_path("/project2"), None, ProjectDetails::default());
         let removed_project = recent_projects.remove_project(0);
-        assert_eq!(removed_project, Some(PathBuf::from("/project1")));
+        assert_eq!(
+            removed_project.map(|p| p.path),
+            Some(project_path("/project2"))
+        );
         assert_eq!(recent_projects.projects.len(), 1);
     }
 
     #[test]
     fn test_remove_project_out_of_bounds() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
+        let mut recent_projects = RecentProjects::default();
         let removed_project = recent_projects.remove_project(0);
         assert_eq!(removed_project, None);
     }
 
     #[test]
     fn test_clear_projects() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
-        recent_projects.add_project(PathBuf::from("/project1"));
-        recent_projects.clear_projects();
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
+        recent_projects.clear_projects(false);
         assert!(recent_projects.projects.is_empty());
     }
 
     #[test]
-    fn test_get_project() {
-        let mut recent_projects = RecentProjects {
-            projects: Vec::new(),
+    fn test_pinned_projects() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.set_limits(HistoryConfig {
+            limit: 2,
+            ..HistoryConfig::default()
+        });
+        recent_projects.add_project_at(
+            project_path("/favourite"),
+            None,
+            ProjectDetails::default(),
+            0,
+        );
+        let index = recent_projects.find_project("favourite").unwrap();
+        assert!(recent_projects.set_pinned(index, true).unwrap().pinned);
+        for i in 1..5 {
+            let path = project_path(&format!("/project{}", i));
+            recent_projects.add_project_at(path, None, ProjectDetails::default(), i);
+        }
+
+        // Kept on top although it is the least recently opened, and not counted in the limit
+        assert_eq!(recent_projects.projects.len(), 3);
+        assert_eq!(recent_projects.projects[0].path, project_path("/favourite"));
+        assert_eq!(
+            recent_projects.sorted(SortOrder::Name, &ProjectFilter::default())[0].0,
+            0
+        );
+
+        recent_projects.clear_projects(false);
+        assert_eq!(recent_projects.projects.len(), 1);
+        recent_projects.clear_projects(true);
+        assert!(recent_projects.projects.is_empty());
+    }
+
+    #[test]
+    fn test_find_project() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(project_path("/a/game"), None, ProjectDetails::default(), 0);
+        recent_projects.add_project_at(project_path("/b/game"), None, ProjectDetails::default(), 1);
+        recent_projects.add_project_at(project_path("/b/tool"), None, ProjectDetails::default(), 2);
+        assert_eq!(recent_projects.find_project("1"), Ok(1));
+        assert_eq!(recent_projects.find_project("tool"), Ok(0));
+        assert!(recent_projects.find_project("3").is_err());
+        assert!(recent_projects.find_project("game").is_err());
+        assert!(recent_projects.find_project("missing").is_err());
+    }
+
+    #[test]
+    fn test_matching_projects() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(
+            project_path("/games/platformer"),
+            None,
+            ProjectDetails::default(),
+            0,
+        );
+        recent_projects.add_project_at(
+            project_path("/games/puzzle"),
+            None,
+            ProjectDetails::default(),
+            1,
+        );
+        recent_projects.add_project_at(
+            project_path("/tools/launcher"),
+            None,
+            ProjectDetails::default(),
+            2,
+        );
+        let index = recent_projects.find_project("platformer").unwrap();
+        recent_projects
+            .set_alias(index, Some("game".to_string()))
+            .unwrap();
+
+        let names = |matches: Vec<usize>| -> Vec<String> {
+            matches
+                .iter()
+                .map(|&i| recent_projects.projects[i].name().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(recent_projects.matching_projects("game")),
+            vec!["game"]
+        );
+        assert_eq!(
+            names(recent_projects.matching_projects("Lau")),
+            vec!["launcher"]
+        );
+        assert_eq!(
+            names(recent_projects.matching_projects("puzzel")),
+            vec!["puzzle"]
+        );
+        assert_eq!(recent_projects.matching_projects("p").len(), 1);
+        assert!(recent_projects.matching_projects("xyz").is_empty());
+    }
+
+    #[test]
+    fn test_set_alias() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(project_path("/a/game"), None, ProjectDetails::default(), 0);
+        recent_projects.add_project_at(project_path("/b/tool"), None, ProjectDetails::default(), 1);
+        assert!(recent_projects
+            .set_alias(0, Some("game".to_string()))
+            .is_err());
+        assert!(recent_projects.set_alias(0, Some("3".to_string())).is_err());
+        assert_eq!(
+            recent_projects
+                .set_alias(0, Some("editor".to_string()))
+                .unwrap()
+                .name(),
+            "editor"
+        );
+        assert_eq!(recent_projects.set_alias(0, None).unwrap().name(), "tool");
+    }
+
+    #[test]
+    fn test_project_ids() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(
+            project_path("/project1"),
+            None,
+            ProjectDetails::default(),
+            0,
+        );
+        recent_projects.add_project_at(
+            project_path("/project2"),
+            None,
+            ProjectDetails::default(),
+            1,
+        );
+        let id = recent_projects.projects[1].id.clone();
+        assert_eq!(id, project_id(&project_path("/project1")));
+        assert_eq!(id.len(), 8);
+        assert_ne!(id, recent_projects.projects[0].id);
+
+        // The ID keeps referring to the same project after the list changes
+        recent_projects.remove_project(0);
+        assert_eq!(recent_projects.find_project(&id), Ok(0));
+
+        recent_projects.save(config_dir).unwrap();
+        let loaded = RecentProjects::load(config_dir).unwrap();
+        assert_eq!(loaded.projects[0].id, id);
+    }
+
+    #[test]
+    fn test_tags_and_filter() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(project_path("/game"), None, of_type("unity"), 0);
+        recent_projects.add_project_at(project_path("/client"), None, of_type("unity"), 1);
+        recent_projects.add_project_at(project_path("/server"), None, of_type("rust"), 2);
+
+        let tags = vec![
+            "client".to_string(),
+            "game".to_string(),
+            "client".to_string(),
+        ];
+        let index = recent_projects.position(&project_path("/game")).unwrap();
+        let project = recent_projects.add_tags(index, &tags).unwrap();
+        assert_eq!(project.tags, vec!["client", "game"]);
+        let index = recent_projects.position(&project_path("/client")).unwrap();
+        recent_projects.add_tags(index, &["client".to_string()]);
+        recent_projects.save(config_dir).unwrap();
+        let mut recent_projects = RecentProjects::load(config_dir).unwrap();
+
+        let filter = ProjectFilter {
+            tags: vec!["client".to_string()],
+            project_type: Some("Unity".to_string()),
+        };
+        let names: Vec<&str> = recent_projects
+            .sorted(SortOrder::Name, &filter)
+            .iter()
+            .map(|(_, p)| p.name())
+            .collect();
+        assert_eq!(names, vec!["client", "game"]);
+
+        let index = recent_projects.position(&project_path("/game")).unwrap();
+        recent_projects.remove_tags(index, &["client".to_string()]);
+        assert_eq!(recent_projects.sorted(SortOrder::Name, &filter).len(), 1);
+    }
+
+    #[test]
+    fn test_prune_missing_projects() {
+        let temp_dir = tempdir().unwrap();
+        let existing = temp_dir.path().join("existing");
+        fs::create_dir(&existing).unwrap();
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(existing.clone(), None, ProjectDetails::default(), 0);
+        recent_projects.add_project_at(
+            temp_dir.path().join("deleted"),
+            None,
+            ProjectDetails::default(),
+            1,
+        );
+        assert!(recent_projects.projects[0].is_missing());
+
+        let pruned = recent_projects.prune();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].name(), "deleted");
+        assert_eq!(recent_projects.projects.len(), 1);
+        assert_eq!(recent_projects.projects[0].path, existing);
+    }
+
+    #[test]
+    fn test_records_project_details() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        fs::write(
+            config_dir.join(RECENT_PROJECTS_FILE),
+            "[[projects]]\npath = \"/game\"\nlast_opened = 100\n",
+        )
+        .unwrap();
+
+        let mut recent_projects = RecentProjects::load(config_dir).unwrap();
+        assert_eq!(recent_projects.projects[0].unity_version, None);
+        let details = ProjectDetails {
+            project_type: Some("unity".to_string()),
+            unity_version: Some("2022.3.5f1".to_string()),
+            git_branch: Some("main".to_string()),
         };
-        recent_projects.add_project(PathBuf::from("/project1"));
+        recent_projects.add_project_at(project_path("/game"), None, details, 200);
+        recent_projects.save(config_dir).unwrap();
+
+        let loaded = RecentProjects::load(config_dir).unwrap();
+        let project = &loaded.projects[0];
+        assert_eq!(project.open_count, 1);
+        assert_eq!(project.last_opened, 200);
+        assert_eq!(project.unity_version.as_deref(), Some("2022.3.5f1"));
+        assert_eq!(project.git_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_duplicate_paths_merged() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        fs::write(
+            config_dir.join(RECENT_PROJECTS_FILE),
+            r#"
+[[projects]]
+path = "/games/game"
+last_opened = 100
+open_count = 2
+tags = ["client"]
+
+[[projects]]
+path = "/games/./tools/../game/"
+last_opened = 200
+open_count = 3
+profile = "work"
+pinned = true
+"#,
+        )
+        .unwrap();
+
+        let mut recent_projects = RecentProjects::load(config_dir).unwrap();
+        assert_eq!(recent_projects.projects.len(), 1);
+        let project = &recent_projects.projects[0];
+        assert_eq!(project.path, project_path("/games/game"));
+        assert_eq!(project.open_count, 5);
+        assert_eq!(project.last_opened, 200);
+        assert_eq!(project.profile.as_deref(), Some("work"));
+        assert_eq!(project.tags, vec!["client"]);
+        assert!(project.pinned);
+
+        let details = ProjectDetails::default();
+        recent_projects.add_project_at(project_path("/games/tools/../game"), None, details, 300);
+        assert_eq!(recent_projects.projects.len(), 1);
+        assert_eq!(recent_projects.projects[0].open_count, 6);
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path().to_path_buf();
+        let limits = HistoryConfig {
+            limit: 0,
+            ..HistoryConfig::default()
+        };
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let config_dir = config_dir.clone();
+                let limits = limits.clone();
+                std::thread::spawn(move || {
+                    RecentProjects::update(&config_dir, &limits, |recent_projects| {
+                        let path = project_path(&format!("/project{}", i));
+                        recent_projects.add_project(path, None, ProjectDetails::default());
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let recent_projects = RecentProjects::load(&config_dir).unwrap();
+        assert_eq!(recent_projects.projects.len(), 8);
+    }
+
+    #[test]
+    fn test_path_mapping() {
+        let mapping: PathMapping = "C:\\dev=/home/me/dev".parse().unwrap();
+        assert_eq!(
+            mapping.apply(Path::new("C:\\dev\\games\\game")),
+            Some(PathBuf::from("/home/me/dev/games/game"))
+        );
+        assert_eq!(
+            mapping.apply(Path::new("C:\\dev")),
+            Some(PathBuf::from("/home/me/dev"))
+        );
+        assert_eq!(mapping.apply(Path::new("C:\\devtools\\tool")), None);
+        assert!("C:\\dev".parse::<PathMapping>().is_err());
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let mut exported = RecentProjects::default();
+        exported.add_project_at(project_path("/old/game"), None, of_type("unity"), 100);
+        exported.add_project_at(project_path("/old/tool"), None, of_type("rust"), 200);
+        exported.set_alias(1, Some("game".to_string())).unwrap();
+        exported.add_tags(0, &["cli".to_string()]);
+        exported.set_pinned(1, true);
+        let data = exported.export().unwrap();
+
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project_at(project_path("/new/tool"), None, of_type("rust"), 300);
+        let mapping = format!(
+            "{}={}",
+            project_path("/old").display(),
+            project_path("/new").display()
+        );
+        let mappings = vec![mapping.parse().unwrap()];
+        assert_eq!(recent_projects.import(&data, &mappings).unwrap(), (1, 1, 0));
+
+        assert_eq!(recent_projects.projects.len(), 2);
+        let game = &recent_projects.projects[0];
+        assert_eq!(game.path, project_path("/new/game"));
+        assert_eq!(game.id, project_id(&project_path("/new/game")));
+        assert_eq!(game.alias.as_deref(), Some("game"));
+        assert!(game.pinned);
+        let tool = &recent_projects.projects[1];
+        assert_eq!(tool.path, project_path("/new/tool"));
+        assert_eq!(tool.open_count, 2);
+        assert_eq!(tool.last_opened, 300);
+        assert_eq!(tool.tags, vec!["cli"]);
+    }
+
+    #[test]
+    fn test_import_over_limit() {
+        let mut exported = RecentProjects::default();
+        for i in 0..3 {
+            let path = project_path(&format!("/imported{}", i));
+            exported.add_project_at(path, None, ProjectDetails::default(), i);
+        }
+        let data = exported.export().unwrap();
+
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.set_limits(HistoryConfig {
+            limit: 3,
+            ..HistoryConfig::default()
+        });
+        for i in 10..12 {
+            let path = project_path(&format!("/project{}", i));
+            recent_projects.add_project_at(path, None, ProjectDetails::default(), i);
+        }
+        assert_eq!(recent_projects.import(&data, &[]).unwrap(), (3, 0, 2));
+        assert_eq!(recent_projects.projects.len(), 3);
+        assert!(recent_projects
+            .projects
+            .iter()
+            .any(|p| p.path == project_path("/imported2")));
+    }
+
+    #[test]
+    fn test_undo() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        let limits = HistoryConfig::default();
+        RecentProjects::update(config_dir, &limits, |recent_projects| {
+            recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
+            recent_projects.add_project(project_path("/project2"), None, ProjectDetails::default());
+        })
+        .unwrap();
+        assert!(!RecentProjects::undo(config_dir, &limits).unwrap());
+
+        RecentProjects::update_undoable(config_dir, &limits, |recent_projects| {
+            recent_projects.remove_project(0)
+        })
+        .unwrap();
+        RecentProjects::update_undoable(config_dir, &limits, |recent_projects| {
+            recent_projects.clear_projects(true)
+        })
+        .unwrap();
+        // Changes that leave the list as it was have nothing to undo
+        RecentProjects::update_undoable(config_dir, &limits, |recent_projects| {
+            recent_projects.remove_project(5)
+        })
+        .unwrap();
+        assert!(RecentProjects::load(config_dir)
+            .unwrap()
+            .projects
+            .is_empty());
+
+        assert!(RecentProjects::undo(config_dir, &limits).unwrap());
+        assert_eq!(RecentProjects::load(config_dir).unwrap().projects.len(), 1);
+        assert!(RecentProjects::undo(config_dir, &limits).unwrap());
+        assert_eq!(RecentProjects::load(config_dir).unwrap().projects.len(), 2);
+        assert!(!RecentProjects::undo(config_dir, &limits).unwrap());
+    }
+
+    #[test]
+    fn test_undo_keeps_a_few_generations() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path();
+        let limits = HistoryConfig::default();
+        for i in 0..UNDO_GENERATIONS + 2 {
+            RecentProjects::update_undoable(config_dir, &limits, |recent_projects| {
+                let path = project_path(&format!("/project{}", i));
+                recent_projects.add_project(path, None, ProjectDetails::default());
+            })
+            .unwrap();
+        }
+
+        let mut undone = 0;
+        while RecentProjects::undo(config_dir, &limits).unwrap() {
+            undone += 1;
+        }
+        assert_eq!(undone, UNDO_GENERATIONS);
+        assert_eq!(RecentProjects::load(config_dir).unwrap().projects.len(), 2);
+    }
+
+    #[test]
+    fn test_get_project() {
+        let mut recent_projects = RecentProjects::default();
+        recent_projects.add_project(project_path("/project1"), None, ProjectDetails::default());
         let project = recent_projects.get_project(0);
-        assert_eq!(project, Some(&PathBuf::from("/project1")));
+        assert_eq!(project.map(|p| &p.path), Some(&project_path("/project1")));
     }
 
     #[test]
     fn test_get_project_out_of_bounds() {
-        let recent_projects = RecentProjects {
-            projects: Vec::new(),
-        };
+        let recent_projects = RecentProjects::default();
         let project = recent_projects.get_project(0);
         assert_eq!(project, None);
     }
@@ -1,22 +1,37 @@
+use crate::config::RustConfig;
+use crate::environment::Environment;
+use crate::launch::{self, StepKind};
+use crate::project_settings::ProjectSettings;
+use crate::template::ProjectVariables;
 use crate::utils;
 use std::path::Path;
-use std::process::Command;
+use std::process::Child;
 
-pub fn open_rust_project(ide_path: &Path, project_path: &Path) {
-    if project_path.exists() && project_path.is_dir() {
-        println!("Opening Rust project: {}", project_path.display());
-        open_in_ide(ide_path, project_path);
-        utils::open_lazygit(project_path);
-    } else {
+/// Runs the launch steps of a Rust project and returns the IDE process if one was started.
+pub fn open_rust_project(
+    config: &RustConfig,
+    project_path: &Path,
+    settings: &ProjectSettings,
+    environment: &Environment,
+) -> Option<Child> {
+    if !(project_path.exists() && project_path.is_dir()) {
         eprintln!("No project directory provided.");
+        return None;
     }
-}
-
-pub fn open_in_ide(ide_path: &Path, project_path: &Path) {
-    if !project_path.exists() {
-        eprintln!("Directory does not exist: {}", project_path.display());
-        return;
+    println!("Opening Rust project: {}", project_path.display());
+    let variables = ProjectVariables::new(project_path);
+    let mut ide = None;
+    for step in launch::enabled_steps(&config.launch, settings) {
+        match &step.kind {
+            StepKind::Ide { args } => match variables.expand_args(args) {
+                Ok(args) => {
+                    ide = utils::open_in_ide(&config.ide_path, project_path, &args, environment)
+                        .or(ide);
+                }
+                Err(e) => eprintln!("Skipping launch step 'ide': {}", e),
+            },
+            kind => launch::run_common_step(kind, project_path, &variables, environment),
+        }
     }
-
-    Command::new(ide_path).arg(project_path).spawn().unwrap();
+    ide
 }
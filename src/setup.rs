@@ -0,0 +1,226 @@
+use crate::config::Config;
+use crate::unity_editors;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Input, Select};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Executable names of editors and IDEs looked up on `PATH`
+const IDE_NAMES: &[&str] = &[
+    "rustrover",
+    "clion",
+    "idea",
+    "code",
+    "codium",
+    "zed",
+    "subl",
+    "fleet",
+    "notepad++",
+];
+
+/// Walks through every path in the configuration, offering detected candidates for each.
+pub fn run(config: &mut Config) {
+    // Like the prompts, on stderr so commands whose output is piped stay clean
+    eprintln!("Setting up devenv. Pick a detected path or enter one manually.");
+    if let Some(path) = prompt_ide_path(&config.rust.ide_path) {
+        config.rust.ide_path = path;
+    }
+    if let Some(path) = prompt_unity_editor_base_path(&config.unity.editor_base_path) {
+        config.unity.editor_base_path = path;
+    }
+    if let Some(path) = prompt_json_editor_path(&config.unity.json_editor_path) {
+        config.unity.json_editor_path = path;
+    }
+}
+
+pub fn prompt_ide_path(current: &Path) -> Option<PathBuf> {
+    prompt_for_path("Rust IDE", current, ide_candidates())
+}
+
+pub fn prompt_unity_editor_base_path(current: &Path) -> Option<PathBuf> {
+    prompt_for_path(
+        "Unity editor base path (the folder containing the version folders)",
+        current,
+        unity_root_candidates(),
+    )
+}
+
+pub fn prompt_json_editor_path(current: &Path) -> Option<PathBuf> {
+    prompt_for_path("JSON editor", current, ide_candidates())
+}
+
+/// Lets the user pick one of `candidates` or type a path, which must exist. Returns `None`
+/// when the current value is kept or the prompt can't be shown.
+fn prompt_for_path(label: &str, current: &Path, candidates: Vec<PathBuf>) -> Option<PathBuf> {
+    let theme = ColorfulTheme::default();
+    let has_current = !current.as_os_str().is_empty();
+
+    let mut items: Vec<String> = candidates.iter().map(|c| c.display().to_string()).collect();
+    let manual_index = items.len();
+    items.push("Enter a path manually".to_string());
+    if has_current {
+        items.push(format!("Keep current ({})", current.display()));
+    }
+
+    let selection = Select::with_theme(&theme)
+        .with_prompt(label)
+        .default(0)
+        .items(&items)
+        .interact()
+        .ok()?;
+
+    if selection < manual_index {
+        return candidates.into_iter().nth(selection);
+    }
+    if selection > manual_index {
+        return None;
+    }
+
+    let input: String = Input::with_theme(&theme)
+        .with_prompt(label)
+        .validate_with(|input: &String| -> Result<(), String> {
+            if Path::new(input.trim()).exists() {
+                Ok(())
+            } else {
+                Err(format!("{} does not exist", input.trim()))
+            }
+        })
+        .interact_text()
+        .ok()?;
+    Some(PathBuf::from(input.trim()))
+}
+
+fn ide_candidates() -> Vec<PathBuf> {
+    let path_dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    let mut candidates = find_executables(&path_dirs, IDE_NAMES);
+    candidates.extend(existing(well_known_ide_paths()));
+    dedup(candidates)
+}
+
+fn unity_root_candidates() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = env_path("ProgramFiles", &["Unity", "Hub", "Editor"])
+        .into_iter()
+        .collect();
+    roots.extend(unity_editors::default_install_roots());
+    dedup(existing(roots))
+}
+
+fn well_known_ide_paths() -> Vec<PathBuf> {
+    let paths = match env::consts::OS {
+        "windows" => vec![
+            env_path(
+                "LOCALAPPDATA",
+                &["Programs", "Microsoft VS Code", "Code.exe"],
+            ),
+            env_path("ProgramFiles", &["Microsoft VS Code", "Code.exe"]),
+            env_path("ProgramFiles", &["Notepad++", "notepad++.exe"]),
+            env_path(
+                "LOCALAPPDATA",
+                &["JetBrains", "Toolbox", "scripts", "rustrover.cmd"],
+            ),
+        ],
+        "macos" => vec![
+            Some(PathBuf::from(
+                "/Applications/RustRover.app/Contents/MacOS/rustrover",
+            )),
+            Some(PathBuf::from(
+                "/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code",
+            )),
+            Some(PathBuf::from("/Applications/Zed.app/Contents/MacOS/cli")),
+        ],
+        _ => vec![
+            Some(PathBuf::from("/snap/bin/code")),
+            Some(PathBuf::from("/snap/bin/rustrover")),
+            env_path(
+                "HOME",
+                &[
+                    ".local",
+                    "share",
+                    "JetBrains",
+                    "Toolbox",
+                    "scripts",
+                    "rustrover",
+                ],
+            ),
+        ],
+    };
+    paths.into_iter().flatten().collect()
+}
+
+/// Looks for any of `names` in `dirs`, trying the usual executable extensions on Windows.
+fn find_executables(dirs: &[PathBuf], names: &[&str]) -> Vec<PathBuf> {
+    let extensions: &[&str] = if cfg!(target_os = "windows") {
+        &["exe", "cmd", "bat"]
+    } else {
+        &[""]
+    };
+
+    let mut found = Vec::new();
+    for name in names {
+        for dir in dirs {
+            for extension in extensions {
+                let candidate = dir.join(name).with_extension(extension);
+                if candidate.is_file() {
+                    found.push(candidate);
+                }
+            }
+        }
+    }
+    found
+}
+
+fn env_path(var: &str, components: &[&str]) -> Option<PathBuf> {
+    let mut path = PathBuf::from(env::var_os(var)?);
+    path.extend(components);
+    Some(path)
+}
+
+fn existing(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    paths.into_iter().filter(|path| path.exists()).collect()
+}
+
+fn dedup(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut unique = Vec::new();
+    for path in paths {
+        if !unique.contains(&path) {
+            unique.push(path);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_executables() {
+        let temp_dir = tempdir().unwrap();
+        let bin = temp_dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        let file_name = if cfg!(target_os = "windows") {
+            "code.exe"
+        } else {
+            "code"
+        };
+        File::create(bin.join(file_name)).unwrap();
+        fs::create_dir(bin.join("zed")).unwrap();
+
+        let found = find_executables(&[temp_dir.path().to_path_buf(), bin.clone()], IDE_NAMES);
+        assert_eq!(found, vec![bin.join(file_name)]);
+    }
+
+    #[test]
+    fn test_dedup_keeps_order() {
+        let paths = vec![
+            PathBuf::from("/b"),
+            PathBuf::from("/a"),
+            PathBuf::from("/b"),
+        ];
+        assert_eq!(dedup(paths), vec![PathBuf::from("/b"), PathBuf::from("/a")]);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::utils;
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Arguments of a launch step, either as a single command line or as a list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Arguments {
+    /// Split like a command line, with placeholder values quoted so they stay one argument.
+    /// Windows rules are used on Windows, where `\` is only special before a `"`, and POSIX
+    /// shell rules elsewhere, where `\` escapes the next character
+    Line(String),
+    /// Passed as they are after the placeholders are expanded
+    List(Vec<String>),
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        Arguments::List(Vec::new())
+    }
+}
+
+impl Arguments {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Arguments::Line(line) => line.trim().is_empty(),
+            Arguments::List(list) => list.is_empty(),
+        }
+    }
+}
+
+/// Values of the `{placeholder}`s available to launch steps for a project.
+#[derive(Debug, Clone)]
+pub struct ProjectVariables {
+    values: BTreeMap<&'static str, Option<String>>,
+}
+
+impl ProjectVariables {
+    pub fn new(project_path: &Path) -> Self {
+        let project_name = project_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        let git_root = utils::find_git_root(project_path).map(|path| path_to_string(&path));
+
+        ProjectVariables {
+            values: BTreeMap::new(),
+        }
+        .with("project_dir", Some(path_to_string(project_path)))
+        .with("project_name", project_name)
+        .with("git_root", git_root)
+        .with("unity_version", None)
+        .with("packages_dir", None)
+        .with("sln_path", None)
+    }
+
+    pub fn with(mut self, name: &'static str, value: Option<String>) -> Self {
+        self.values.insert(name, value);
+        self
+    }
+
+    /// Replaces every placeholder with its value as is.
+    pub fn expand(&self, template: &str) -> Result<String, String> {
+        self.substitute(template, |value| value.to_string())
+    }
+
+    pub fn expand_path(&self, template: &Path) -> Result<PathBuf, String> {
+        self.expand(&template.to_string_lossy()).map(PathBuf::from)
+    }
+
+    /// Expands arguments into the list passed to the spawned process.
+    pub fn expand_args(&self, args: &Arguments) -> Result<Vec<String>, String> {
+        match args {
+            Arguments::Line(line) if cfg!(windows) => {
+                let line = self.substitute(line, quote_windows)?;
+                Ok(split_windows(&line))
+            }
+            Arguments::Line(line) => {
+                let line = self.substitute(line, |value| shell_words::quote(value).into_owned())?;
+                shell_words::split(&line)
+                    .map_err(|e| format!("Failed to parse arguments '{}': {}", line, e))
+            }
+            Arguments::List(list) => list.iter().map(|arg| self.expand(arg)).collect(),
+        }
+    }
+
+    fn substitute(&self, template: &str, quote: impl Fn(&str) -> String) -> Result<String, String> {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let name = after
+                .find('}')
+                .map(|end| &after[..end])
+                .filter(|name| is_placeholder_name(name));
+            match name {
+                Some(name) => {
+                    let value = match self.values.get(name) {
+                        Some(Some(value)) => value,
+                        Some(None) => {
+                            return Err(format!(
+                                "Placeholder {{{}}} is not available for this project",
+                                name
+                            ))
+                        }
+                        None => return Err(format!("Unknown placeholder {{{}}}", name)),
+                    };
+                    result.push_str(&quote(value));
+                    rest = &after[name.len() + 1..];
+                }
+                None => {
+                    result.push('{');
+                    rest = after;
+                }
+            }
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+/// Splits a command line the way the Microsoft C runtime does: backslashes are literal unless
+/// they precede a `"`, and `"` groups arguments containing whitespace.
+fn split_windows(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' if !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if_eq(&'\\').is_some() {
+                    backslashes += 1;
+                }
+                if chars.peek() == Some(&'"') {
+                    current.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 1 {
+                        current.push('"');
+                        chars.next();
+                    }
+                } else {
+                    current.extend(std::iter::repeat_n('\\', backslashes));
+                }
+                in_arg = true;
+            }
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Quotes `value` so that `split_windows` reads it back as a single argument.
+fn quote_windows(value: &str) -> String {
+    if !value.is_empty() && !value.contains([' ', '\t', '"']) {
+        return value.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in value.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                backslashes = 0;
+            }
+        }
+        if c != '\\' {
+            quoted.push(c);
+        }
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+}
+
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> ProjectVariables {
+        ProjectVariables::new(Path::new("/home/me/my game"))
+            .with("unity_version", Some("2022.3.1f1".to_string()))
+    }
+
+    #[test]
+    fn test_expand() {
+        let expanded = variables()
+            .expand("{project_dir}/Logs/{project_name}.log")
+            .unwrap();
+        assert_eq!(expanded, "/home/me/my game/Logs/my game.log");
+    }
+
+    #[test]
+    fn test_expand_leaves_other_braces() {
+        let expanded = variables().expand("{\"a\": 1} {}").unwrap();
+        assert_eq!(expanded, "{\"a\": 1} {}");
+    }
+
+    #[test]
+    fn test_expand_unknown_placeholder() {
+        assert!(variables().expand("{unknown}").is_err());
+    }
+
+    #[test]
+    fn test_expand_unavailable_placeholder() {
+        assert!(variables().expand("{sln_path}").is_err());
+    }
+
+    #[test]
+    fn test_expand_args_line_quotes_values() {
+        let args = Arguments::Line("-logFile {project_dir}/Logs/editor.log -v".to_string());
+        let expanded = variables().expand_args(&args).unwrap();
+        assert_eq!(
+            expanded,
+            vec!["-logFile", "/home/me/my game/Logs/editor.log", "-v"]
+        );
+    }
+
+    #[test]
+    fn test_split_windows_keeps_backslashes() {
+        assert_eq!(
+            split_windows(r#"-logFile C:\Logs\editor.log  "C:\My Games\\" a\"b"#),
+            vec!["-logFile", r"C:\Logs\editor.log", r"C:\My Games\", r#"a"b"#]
+        );
+    }
+
+    #[test]
+    fn test_quote_windows_round_trips() {
+        for value in [r"C:\My Games\", r#"say "hi""#, "", "plain", r"C:\Logs\"] {
+            assert_eq!(split_windows(&quote_windows(value)), vec![value]);
+        }
+    }
+
+    #[test]
+    fn test_expand_args_list() {
+        let args = Arguments::List(vec!["--version".to_string(), "{unity_version}".to_string()]);
+        let expanded = variables().expand_args(&args).unwrap();
+        assert_eq!(expanded, vec!["--version", "2022.3.1f1"]);
+    }
+}
@@ -1,25 +1,68 @@
+use crate::config::UnityConfig;
+use crate::environment::Environment;
+use crate::launch::{self, StepKind};
+use crate::project_settings::ProjectSettings;
+use crate::template::ProjectVariables;
+use crate::unity_editors::UnityEditors;
 use crate::utils;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command};
 
-pub fn open_unity_project(unity_hub_path: PathBuf, project_path: &Path, json_editor_path: &Path) {
-    open_in_unity(unity_hub_path, project_path);
-    open_sln_file(project_path);
-    utils::open_lazygit(project_path);
+/// Runs the launch steps of a Unity project and returns the editor process if one was
+/// started.
+pub fn open_unity_project(
+    config: &UnityConfig,
+    project_path: &Path,
+    settings: &ProjectSettings,
+    environment: &Environment,
+) -> Option<Child> {
     let packages_path = project_path.join("Packages");
-    utils::open_directory(&packages_path);
-    let packages = get_packages(&packages_path);
-    for package in &packages {
-        utils::open_lazygit(package);
+    let variables = ProjectVariables::new(project_path)
+        .with("unity_version", get_unity_version(project_path))
+        .with(
+            "packages_dir",
+            Some(packages_path.to_string_lossy().into_owned()),
+        )
+        .with(
+            "sln_path",
+            find_sln_file(project_path).map(|path| path.to_string_lossy().into_owned()),
+        );
+    let mut ide = None;
+    for step in launch::enabled_steps(&config.launch, settings) {
+        match &step.kind {
+            StepKind::Ide { args } => match variables.expand_args(args) {
+                Ok(args) => {
+                    let editors = UnityEditors::discover(&config.editor_roots());
+                    ide = open_in_unity(&editors, project_path, &args, environment).or(ide);
+                }
+                Err(e) => eprintln!("Skipping launch step 'ide': {}", e),
+            },
+            StepKind::Solution => open_sln_file(project_path, environment),
+            StepKind::PackageGitUi => {
+                for package in &get_packages(&packages_path) {
+                    utils::open_lazygit(package, environment);
+                }
+            }
+            StepKind::PackageManifests => {
+                let mut json_paths: Vec<PathBuf> = get_packages(&packages_path)
+                    .iter()
+                    .map(|p| p.join("package.json"))
+                    .collect();
+                json_paths.insert(0, packages_path.join("manifest.json"));
+                open_json(&config.json_editor_path, &json_paths, environment);
+            }
+            kind => launch::run_common_step(kind, project_path, &variables, environment),
+        }
     }
-    let mut json_paths: Vec<PathBuf> = packages.iter().map(|p| p.join("package.json")).collect();
-    json_paths.insert(0, packages_path.join("manifest.json"));
-    open_json(json_editor_path, &json_paths);
+    ide
 }
 
-fn open_json(json_editor_path: &Path, json_paths: &Vec<PathBuf>) {
-    let result = Command::new(json_editor_path).args(json_paths).spawn();
+fn open_json(json_editor_path: &Path, json_paths: &Vec<PathBuf>, environment: &Environment) {
+    let result = environment
+        .apply(&mut Command::new(json_editor_path))
+        .args(json_paths)
+        .spawn();
     match result {
         Ok(_) => println!("Opened package.json for {:?}", json_paths),
         Err(e) => eprintln!("Failed to json file for {:?}. Error: {}", json_paths, e),
@@ -47,17 +90,22 @@ pub fn get_packages(packages_path: &Path) -> Vec<PathBuf> {
     packages
 }
 
-pub fn open_sln_file(project_path: &Path) {
-    for entry in fs::read_dir(project_path).unwrap() {
-        let entry = entry.unwrap();
-        let file_path = entry.path();
-        if file_path.extension().unwrap_or_default() == "sln" {
+pub fn find_sln_file(project_path: &Path) -> Option<PathBuf> {
+    fs::read_dir(project_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().unwrap_or_default() == "sln")
+}
+
+pub fn open_sln_file(project_path: &Path, environment: &Environment) {
+    match find_sln_file(project_path) {
+        Some(file_path) => {
             println!("Opening solution file: {}", file_path.display());
-            utils::open_file(&file_path);
-            return;
+            utils::open_file(&file_path, environment);
         }
+        None => eprintln!("No .sln file found in the project directory."),
     }
-    eprintln!("No .sln file found in the project directory.")
 }
 
 pub fn get_unity_version(project_path: &Path) -> Option<String> {
@@ -75,44 +123,60 @@ pub fn get_unity_version(project_path: &Path) -> Option<String> {
     None
 }
 
-pub fn get_unity_editor_path(unity_hub_path: PathBuf, unity_version: &str) -> PathBuf {
-    unity_hub_path
-        .join(unity_version)
-        .join("Editor")
-        .join("Unity.exe")
-}
-
-pub fn open_in_unity(unity_hub_path: PathBuf, project_path: &Path) {
+pub fn open_in_unity(
+    editors: &UnityEditors,
+    project_path: &Path,
+    args: &[String],
+    environment: &Environment,
+) -> Option<Child> {
     if !project_path.exists() {
         eprintln!(
             "Project directory does not exist: {}",
             project_path.display()
         );
-        return;
+        return None;
     }
 
     match get_unity_version(project_path) {
         Some(unity_version) => {
-            let unity_executable_path = get_unity_editor_path(unity_hub_path, &unity_version);
-            let result = Command::new(unity_executable_path)
+            let Some(editor) = editors.find(&unity_version) else {
+                eprintln!(
+                    "Unity {} is not installed. Installed versions: {}",
+                    unity_version,
+                    editors.versions().join(", ")
+                );
+                return None;
+            };
+            let result = environment
+                .apply(&mut Command::new(&editor.executable))
                 .arg("-projectPath")
                 .arg(project_path)
+                .args(args)
                 .spawn();
 
             match result {
-                Ok(_) => println!(
-                    "Opened Unity project with version {}: {}",
-                    unity_version,
-                    project_path.display()
-                ),
-                Err(e) => eprintln!(
-                    "Failed to open Unity project: {}. Error: {}",
-                    project_path.display(),
-                    e
-                ),
+                Ok(child) => {
+                    println!(
+                        "Opened Unity project with version {}: {}",
+                        unity_version,
+                        project_path.display()
+                    );
+                    Some(child)
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Failed to open Unity project: {}. Error: {}",
+                        project_path.display(),
+                        e
+                    );
+                    None
+                }
             }
         }
-        None => eprintln!("Failed to read Unity version from ProjectVersion.txt"),
+        None => {
+            eprintln!("Failed to read Unity version from ProjectVersion.txt");
+            None
+        }
     }
 }
 
@@ -141,7 +205,7 @@ mod tests {
         fs::create_dir(&package3_path).unwrap();
         // No package.json file in package3
 
-        let packages = get_packages(&packages_path);
+        let packages = get_packages(packages_path);
         assert_eq!(packages.len(), 2);
         assert!(packages.contains(&package1_path));
         assert!(packages.contains(&package2_path));
@@ -152,7 +216,7 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let packages_path = temp_dir.path();
 
-        let packages = get_packages(&packages_path);
+        let packages = get_packages(packages_path);
         assert!(packages.is_empty());
     }
 
@@ -0,0 +1,282 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An installed Unity editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnityEditor {
+    pub version: String,
+    pub executable: PathBuf,
+}
+
+/// Every Unity editor found in the install roots and the Unity Hub data.
+#[derive(Debug, Default)]
+pub struct UnityEditors {
+    editors: Vec<UnityEditor>,
+}
+
+impl UnityEditors {
+    /// Scans `roots` followed by the Unity Hub install locations and manually located editors.
+    /// The first editor found for a version wins.
+    pub fn discover(roots: &[PathBuf]) -> Self {
+        let hub_dir = hub_config_dir();
+        let mut all_roots = roots.to_vec();
+        if let Some(path) = hub_dir.as_deref().and_then(read_secondary_install_path) {
+            all_roots.push(path);
+        }
+        all_roots.extend(default_install_roots());
+
+        let mut editors = UnityEditors::default();
+        for root in &all_roots {
+            for editor in scan_root(root) {
+                editors.add(editor);
+            }
+        }
+        if let Some(hub_dir) = &hub_dir {
+            for editor in read_hub_editors(hub_dir) {
+                editors.add(editor);
+            }
+        }
+        editors
+    }
+
+    fn add(&mut self, editor: UnityEditor) {
+        if self.find(&editor.version).is_none() {
+            self.editors.push(editor);
+        }
+    }
+
+    pub fn find(&self, version: &str) -> Option<&UnityEditor> {
+        self.editors.iter().find(|editor| editor.version == version)
+    }
+
+    pub fn versions(&self) -> Vec<&str> {
+        self.editors.iter().map(|e| e.version.as_str()).collect()
+    }
+}
+
+/// Locations Unity Hub installs editors to by default on the current platform.
+pub fn default_install_roots() -> Vec<PathBuf> {
+    match env::consts::OS {
+        "windows" => vec![PathBuf::from(r"C:\Program Files\Unity\Hub\Editor")],
+        "macos" => vec![PathBuf::from("/Applications/Unity/Hub/Editor")],
+        _ => env::var_os("HOME")
+            .map(|home| vec![PathBuf::from(home).join("Unity/Hub/Editor")])
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns the editor executable inside a version folder, trying the Windows, Linux and
+/// macOS layouts.
+pub fn editor_executable(version_dir: &Path) -> Option<PathBuf> {
+    [
+        version_dir.join("Editor").join("Unity.exe"),
+        version_dir.join("Editor").join("Unity"),
+        version_dir.join("Unity.app/Contents/MacOS/Unity"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Finds editors in a root that holds one folder per version.
+fn scan_root(root: &Path) -> Vec<UnityEditor> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut editors: Vec<UnityEditor> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let executable = editor_executable(&entry.path())?;
+            Some(UnityEditor {
+                version: entry.file_name().to_string_lossy().into_owned(),
+                executable,
+            })
+        })
+        .collect();
+    editors.sort_by(|a, b| a.version.cmp(&b.version));
+    editors
+}
+
+fn hub_config_dir() -> Option<PathBuf> {
+    let dir = match env::consts::OS {
+        "windows" => PathBuf::from(env::var_os("APPDATA")?).join("UnityHub"),
+        "macos" => PathBuf::from(env::var_os("HOME")?).join("Library/Application Support/UnityHub"),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config/UnityHub"),
+    };
+    Some(dir)
+}
+
+/// Reads the custom install location set in Unity Hub, stored as a single JSON string.
+fn read_secondary_install_path(hub_dir: &Path) -> Option<PathBuf> {
+    let data = fs::read_to_string(hub_dir.join("secondaryInstallPath.json")).ok()?;
+    let path: String = serde_json::from_str(&data).ok()?;
+    if path.trim().is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Reads editors that were located manually in Unity Hub. Both the `editors-v2.json` list
+/// and the older `editors.json` map are supported.
+fn read_hub_editors(hub_dir: &Path) -> Vec<UnityEditor> {
+    let mut editors = Vec::new();
+    for file in ["editors-v2.json", "editors.json"] {
+        let Ok(data) = fs::read_to_string(hub_dir.join(file)) else {
+            continue;
+        };
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&data) else {
+            eprintln!("Failed to parse Unity Hub {}", file);
+            continue;
+        };
+        let entries: Vec<&serde_json::Value> = match &json {
+            serde_json::Value::Object(map) => match map.get("data") {
+                Some(serde_json::Value::Array(list)) => list.iter().collect(),
+                _ => map.values().collect(),
+            },
+            serde_json::Value::Array(list) => list.iter().collect(),
+            _ => Vec::new(),
+        };
+        editors.extend(entries.into_iter().filter_map(parse_hub_editor));
+    }
+    editors
+}
+
+fn parse_hub_editor(entry: &serde_json::Value) -> Option<UnityEditor> {
+    let version = entry.get("version")?.as_str()?.to_string();
+    let location = match entry.get("location")? {
+        serde_json::Value::Array(locations) => locations.first()?.as_str()?,
+        location => location.as_str()?,
+    };
+    let executable = hub_location_executable(Path::new(location))?;
+    Some(UnityEditor {
+        version,
+        executable,
+    })
+}
+
+/// Resolves a location stored by Unity Hub to the editor executable. On macOS the location
+/// is the `Unity.app` bundle rather than the executable inside it.
+fn hub_location_executable(location: &Path) -> Option<PathBuf> {
+    if location.is_file() {
+        return Some(location.to_path_buf());
+    }
+    if location
+        .extension()
+        .is_some_and(|extension| extension == "app")
+    {
+        let executable = location.join("Contents/MacOS/Unity");
+        return executable.is_file().then_some(executable);
+    }
+    editor_executable(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    fn create_editor(root: &Path, version: &str, layout: &str) -> PathBuf {
+        let executable = root.join(version).join(layout);
+        fs::create_dir_all(executable.parent().unwrap()).unwrap();
+        File::create(&executable).unwrap();
+        executable
+    }
+
+    #[test]
+    fn test_scan_root_platform_layouts() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let windows = create_editor(root, "2021.3.1f1", "Editor/Unity.exe");
+        let linux = create_editor(root, "2022.3.5f1", "Editor/Unity");
+        let macos = create_editor(root, "6000.0.1f1", "Unity.app/Contents/MacOS/Unity");
+        fs::create_dir(root.join("not-an-editor")).unwrap();
+
+        let editors = scan_root(root);
+        assert_eq!(
+            editors,
+            vec![
+                UnityEditor {
+                    version: "2021.3.1f1".to_string(),
+                    executable: windows,
+                },
+                UnityEditor {
+                    version: "2022.3.5f1".to_string(),
+                    executable: linux,
+                },
+                UnityEditor {
+                    version: "6000.0.1f1".to_string(),
+                    executable: macos,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_prefers_configured_roots() {
+        let temp_dir = tempdir().unwrap();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        let expected = create_editor(&first, "2022.3.5f1", "Editor/Unity");
+        create_editor(&second, "2022.3.5f1", "Editor/Unity");
+
+        let editors = UnityEditors::discover(&[first, second]);
+        assert_eq!(editors.find("2022.3.5f1").unwrap().executable, expected);
+        assert!(editors.find("2019.4.1f1").is_none());
+    }
+
+    #[test]
+    fn test_read_hub_files() {
+        let temp_dir = tempdir().unwrap();
+        let hub_dir = temp_dir.path();
+        let v1 = create_editor(hub_dir, "custom-a", "Editor/Unity");
+        let v2 = create_editor(hub_dir, "custom-b", "Editor/Unity");
+        fs::write(
+            hub_dir.join("editors.json"),
+            serde_json::json!({
+                "2021.3.1f1": { "version": "2021.3.1f1", "location": [v1], "manual": true }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::write(
+            hub_dir.join("editors-v2.json"),
+            serde_json::json!({
+                "schema_version": "v2",
+                "data": [{ "version": "2022.3.5f1", "location": [v2], "manual": true }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::write(hub_dir.join("secondaryInstallPath.json"), "\"D:\\\\Unity\"").unwrap();
+
+        let editors = read_hub_editors(hub_dir);
+        let versions: Vec<&str> = editors.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["2022.3.5f1", "2021.3.1f1"]);
+        assert_eq!(
+            read_secondary_install_path(hub_dir),
+            Some(PathBuf::from("D:\\Unity"))
+        );
+    }
+
+    #[test]
+    fn test_hub_location_app_bundle() {
+        let temp_dir = tempdir().unwrap();
+        let executable = create_editor(
+            temp_dir.path(),
+            "6000.0.1f1",
+            "Unity.app/Contents/MacOS/Unity",
+        );
+        let bundle = temp_dir.path().join("6000.0.1f1/Unity.app");
+
+        let entry = serde_json::json!({ "version": "6000.0.1f1", "location": [bundle] });
+        let editor = parse_hub_editor(&entry).unwrap();
+        assert_eq!(editor.executable, executable);
+
+        let empty_bundle = temp_dir.path().join("Empty.app");
+        fs::create_dir(&empty_bundle).unwrap();
+        let entry = serde_json::json!({ "version": "6000.0.2f1", "location": [empty_bundle] });
+        assert!(parse_hub_editor(&entry).is_none());
+    }
+}
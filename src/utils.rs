@@ -1,17 +1,174 @@
-use std::io;
+use crate::environment::Environment;
+use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::{Path, PathBuf};
-use std::process::Command;
-
-pub fn prompt_user_for_path(prompt: &str) -> PathBuf {
-    print!("{}", prompt);
-    io::stdout().flush().unwrap();
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
-    PathBuf::from(input.trim())
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command};
+use std::{env, fs, io};
+
+/// Expands a leading `~` to the home directory and `$VAR` or `${VAR}` to the value of the
+/// environment variable. Variables that aren't set are left as they are.
+pub fn expand_path(path: &Path) -> PathBuf {
+    let Some(path) = path.to_str() else {
+        return path.to_path_buf();
+    };
+
+    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE")).ok();
+    let path = match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{}", home, rest)
+        }
+        _ => path.to_string(),
+    };
+
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path.as_str();
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, consumed) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match env::var(name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => expanded.push_str(&rest[start..start + 1 + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+    expanded.push_str(rest);
+    PathBuf::from(expanded)
+}
+
+/// Makes `path` absolute and resolves symlinks, `.` and `..`. Paths that don't exist are
+/// normalised without following symlinks.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    match fs::canonicalize(path) {
+        Ok(canonical) => strip_verbatim_prefix(canonical),
+        Err(_) => {
+            let absolute = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                env::current_dir()
+                    .map(|dir| dir.join(path))
+                    .unwrap_or_else(|_| path.to_path_buf())
+            };
+            let mut normalized = PathBuf::new();
+            for component in absolute.components() {
+                match component {
+                    Component::CurDir => {}
+                    Component::ParentDir => {
+                        normalized.pop();
+                    }
+                    other => normalized.push(other.as_os_str()),
+                }
+            }
+            normalized
+        }
+    }
+}
+
+/// Canonicalising on Windows gives `\\?\C:\...` paths, which many programs don't accept.
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    match path.to_str().and_then(|p| p.strip_prefix(r"\\?\")) {
+        Some(stripped) if !stripped.starts_with("UNC") => PathBuf::from(stripped),
+        _ => path,
+    }
+}
+
+/// Key to compare paths by, ignoring case on the platforms whose file systems are
+/// case-insensitive by default.
+pub fn path_key(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    match env::consts::OS {
+        "windows" | "macos" => path.to_lowercase(),
+        _ => path.into_owned(),
+    }
+}
+
+/// Takes an exclusive advisory lock on a `.lock` file next to `path`, waiting for other
+/// processes to release it first. The lock is released when the returned file is dropped.
+pub fn lock_file(path: &Path) -> io::Result<File> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Replaces the contents of `path` by writing a temporary file next to it and renaming it
+/// over the original, so readers never see a partially written file. A symlink is followed,
+/// so the file it points to is replaced rather than the link, and the original permissions
+/// are kept.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let path = resolve_symlink(path)?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents)?;
+    if let Ok(metadata) = fs::metadata(&path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+    file.as_file().sync_all()?;
+    file.persist(&path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// The file `path` points to if it is a symlink, which may not exist yet, or `path` itself.
+fn resolve_symlink(path: &Path) -> io::Result<PathBuf> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path).or_else(|_| {
+            let target = fs::read_link(path)?;
+            Ok(path.parent().unwrap_or(Path::new("")).join(target))
+        }),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Opens the project in the IDE and returns the IDE process if it was started.
+pub fn open_in_ide(
+    ide_path: &Path,
+    project_path: &Path,
+    args: &[String],
+    environment: &Environment,
+) -> Option<Child> {
+    if !project_path.exists() {
+        eprintln!("Directory does not exist: {}", project_path.display());
+        return None;
+    }
+
+    let result = environment
+        .apply(&mut Command::new(ide_path))
+        .arg(project_path)
+        .args(args)
+        .spawn();
+    match result {
+        Ok(child) => {
+            println!("Opened IDE for {}", project_path.display());
+            Some(child)
+        }
+        Err(e) => {
+            eprintln!("Failed to open IDE: {}. Error: {}", ide_path.display(), e);
+            None
+        }
+    }
 }
 
-pub fn open_file(path: &Path) {
+/// Opens the file at `path` with its default application.
+pub fn open_file(path: &Path, environment: &Environment) {
     if !path.exists() {
         eprintln!("File does not exist: {}", path.display());
         return;
@@ -19,18 +176,22 @@ pub fn open_file(path: &Path) {
 
     #[cfg(target_os = "windows")]
     {
-        let result = Command::new("cmd")
-            .args(["/C", "start", "", path.to_str().unwrap()])
-            .spawn();
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(path);
+        let result = environment.apply(&mut command).spawn();
 
         match result {
             Ok(_) => println!("Opened file {}", path.display()),
             Err(e) => eprintln!("Error opening file: {}, Error: {}", path.display(), e),
         }
     }
+
+    // Only supported on Windows, other platforms skip the step
+    #[cfg(not(target_os = "windows"))]
+    let _ = environment;
 }
 
-pub fn open_directory(path: &Path) {
+pub fn open_directory(path: &Path, environment: &Environment) {
     if !path.exists() {
         eprintln!("Directory does not exist: {}", path.display());
         return;
@@ -38,20 +199,58 @@ pub fn open_directory(path: &Path) {
 
     #[cfg(target_os = "windows")]
     {
-        let result = Command::new("explorer").arg(path).spawn();
+        let mut command = Command::new("explorer");
+        command.arg(path);
+        let result = environment.apply(&mut command).spawn();
 
         match result {
             Ok(_) => println!("Opened directory {}", path.display()),
             Err(e) => eprintln!("Error opening directory: {}, Error: {}", path.display(), e),
         }
     }
+
+    // Only supported on Windows, other platforms skip the step
+    #[cfg(not(target_os = "windows"))]
+    let _ = environment;
 }
 
 pub fn is_git_repository(path: &Path) -> bool {
     path.join(".git").exists()
 }
 
-pub fn open_lazygit(path: &Path) {
+/// Returns the closest directory, starting with `path` itself, that is a Git repository.
+pub fn find_git_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| is_git_repository(ancestor))
+        .map(Path::to_path_buf)
+}
+
+/// Returns the checked out branch of the repository containing `path`, or the abbreviated
+/// commit when the HEAD is detached.
+pub fn git_branch(path: &Path) -> Option<String> {
+    let git_path = find_git_root(path)?.join(".git");
+    // Worktrees and submodules have a `.git` file pointing to the actual git directory
+    let git_dir = if git_path.is_file() {
+        let contents = fs::read_to_string(&git_path).ok()?;
+        let dir = PathBuf::from(contents.strip_prefix("gitdir:")?.trim());
+        git_path.parent()?.join(dir)
+    } else {
+        git_path
+    };
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        ),
+        None => head.get(..7).map(str::to_string),
+    }
+}
+
+pub fn open_lazygit(path: &Path, environment: &Environment) {
     if !path.exists() {
         eprintln!("Directory does not exist: {}", path.display());
         return;
@@ -73,17 +272,103 @@ pub fn open_lazygit(path: &Path) {
 
     #[cfg(target_os = "windows")]
     {
-        Command::new("cmd")
-            .args([
-                "/C",
-                "start",
-                "cmd",
-                "/K",
-                "lazygit",
-                "-p",
-                git_path.to_str().unwrap(),
-            ])
-            .spawn()
-            .unwrap();
+        let mut command = Command::new("cmd");
+        command
+            .args(["/C", "start", "cmd", "/K", "lazygit", "-p"])
+            .arg(git_path);
+        let result = environment.apply(&mut command).spawn();
+
+        match result {
+            Ok(_) => println!("Opened lazygit for {}", git_path.display()),
+            Err(e) => eprintln!(
+                "Error opening lazygit: {}, Error: {}",
+                git_path.display(),
+                e
+            ),
+        }
+    }
+
+    // Only supported on Windows, other platforms skip the step
+    #[cfg(not(target_os = "windows"))]
+    let _ = (git_path, environment);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_canonical_path() {
+        let temp_dir = tempdir().unwrap();
+        let project = temp_dir.path().join("game");
+        fs::create_dir(&project).unwrap();
+        let canonical = fs::canonicalize(&project).unwrap();
+        assert_eq!(canonical_path(&project.join("../game/.")), canonical);
+
+        // Paths that don't exist are only normalised
+        let missing = temp_dir.path().join("missing");
+        assert_eq!(
+            canonical_path(&missing.join("./old/../game/")),
+            missing.join("game")
+        );
+        assert!(canonical_path(Path::new("relative")).is_absolute());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_canonical_path_resolves_symlinks() {
+        let temp_dir = tempdir().unwrap();
+        let project = temp_dir.path().join("game");
+        fs::create_dir(&project).unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&project, &link).unwrap();
+        assert_eq!(canonical_path(&link), canonical_path(&project));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_follows_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("dotfiles/config.toml");
+        fs::create_dir(target.parent().unwrap()).unwrap();
+        fs::write(&target, "old").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o644)).unwrap();
+        let link = temp_dir.path().join("config.toml");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, b"new").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+
+    #[test]
+    fn test_git_branch() {
+        let temp_dir = tempdir().unwrap();
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/feature/login\n").unwrap();
+        assert_eq!(
+            git_branch(&repo.join("src")).as_deref(),
+            Some("feature/login")
+        );
+
+        fs::write(repo.join(".git/HEAD"), "3f9c21ab0d5e6f7a8b9c\n").unwrap();
+        assert_eq!(git_branch(&repo).as_deref(), Some("3f9c21a"));
+
+        // A worktree points to its git directory from a `.git` file
+        let worktree = temp_dir.path().join("worktree");
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(repo.join("HEAD"), "ref: refs/heads/release\n").unwrap();
+        fs::write(worktree.join(".git"), "gitdir: ../repo\n").unwrap();
+        assert_eq!(git_branch(&worktree).as_deref(), Some("release"));
     }
 }